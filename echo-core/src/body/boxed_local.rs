@@ -39,6 +39,10 @@ impl Body for LocalBoxBody {
         self.body.as_mut().poll_frame(cx)
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         self.body.size_hint()
     }
@@ -49,7 +49,7 @@ impl<B: Body> Future for Collect<B> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Collected {
     bufs: BufList<Bytes>,
     trailers: Option<HeaderMap>,
@@ -79,7 +79,7 @@ impl Collected {
 
         if let Ok(trailers) = frame.into_trailers() {
             if let Some(cur) = &mut self.trailers {
-                cur.extend(trailers.into_iter());
+                cur.extend(trailers);
             } else {
                 self.trailers = Some(trailers);
             }
@@ -104,12 +104,3 @@ impl Body for Collected {
         Poll::Ready(Some(Ok(frame)))
     }
 }
-
-impl Default for Collected {
-    fn default() -> Self {
-        Self {
-            bufs: BufList::default(),
-            trailers: None,
-        }
-    }
-}
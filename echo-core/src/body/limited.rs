@@ -58,6 +58,10 @@ where
         Poll::Ready(res)
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         match u64::try_from(self.remaining) {
             Ok(n) => {
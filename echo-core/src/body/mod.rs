@@ -34,6 +34,11 @@ pub trait Body {
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>>;
 
+    /// 返回 `true` 时表示不会再产生任何帧，调用者可以不再调用 `poll_frame`。
+    fn is_end_stream(&self) -> bool {
+        false
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::default()
     }
@@ -52,6 +57,10 @@ where
         Pin::new(&mut **self).poll_frame(cx)
     }
 
+    fn is_end_stream(&self) -> bool {
+        (**self).is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         (**self).size_hint()
     }
@@ -71,6 +80,10 @@ where
         self.get_mut().as_mut().poll_frame(cx)
     }
 
+    fn is_end_stream(&self) -> bool {
+        (**self).is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         (**self).size_hint()
     }
@@ -89,6 +102,10 @@ where
         Pin::new(&mut **self).poll_frame(cx)
     }
 
+    fn is_end_stream(&self) -> bool {
+        (**self).is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         (**self).size_hint()
     }
@@ -104,6 +121,10 @@ impl Body for () {
         Poll::Ready(None)
     }
 
+    fn is_end_stream(&self) -> bool {
+        true
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(0)
     }
@@ -125,6 +146,10 @@ impl Body for &'static [u8] {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -146,6 +171,10 @@ impl Body for Vec<u8> {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -164,6 +193,10 @@ impl Body for Cow<'static, [u8]> {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -185,6 +218,10 @@ impl Body for &'static str {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -206,6 +243,10 @@ impl Body for String {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -224,6 +265,10 @@ impl Body for Cow<'static, str> {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
@@ -243,6 +288,10 @@ impl Body for Bytes {
         }
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.is_empty()
+    }
+
     fn size_hint(&self) -> SizeHint {
         SizeHint::with_exact(self.len() as u64)
     }
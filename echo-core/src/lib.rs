@@ -1,5 +1,4 @@
 #![forbid(unsafe_code)]
-#![deny(private_in_public)]
 #![deny(unreachable_pub)]
 #![warn(missing_debug_implementations)]
 
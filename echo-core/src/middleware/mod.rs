@@ -1,7 +1,15 @@
 mod middleware_fn;
+mod stack;
 
 pub use middleware_fn::{middleware_fn, MiddlewareFn};
 
+/// 将服务变换为另一个服务。
+///
+/// 元组也实现了该特征，可以将多个中间件组合为一个可复用的中间件栈。元组中靠前的中间件位于外层，
+/// 先处理请求、后处理响应，即 `service.with((a, b, c))` 等价于
+/// `service.with(c).with(b).with(a)`。中间件实现了 `Debug` 时，元组的 `Debug` 输出即为从外到内的顺序。
+///
+/// `()` 不做任何变换，`Option<M>` 为 `None` 时也不做任何变换。
 pub trait Middleware<S> {
     type Service;
 
@@ -0,0 +1,155 @@
+use crate::service::Either;
+
+use super::Middleware;
+
+/// 空元组不做任何变换。
+impl<S> Middleware<S> for () {
+    type Service = S;
+
+    #[inline]
+    fn transform(self, service: S) -> Self::Service {
+        service
+    }
+}
+
+/// 为 `None` 时不做任何变换，因此可以根据配置决定是否应用中间件。
+///
+/// 变换后的服务为 [`Either`]，要求中间件不改变服务的响应和错误类型。
+impl<S, M> Middleware<S> for Option<M>
+where
+    M: Middleware<S>,
+{
+    type Service = Either<M::Service, S>;
+
+    #[inline]
+    fn transform(self, service: S) -> Self::Service {
+        match self {
+            Some(middleware) => Either::Left(middleware.transform(service)),
+            None => Either::Right(service),
+        }
+    }
+}
+
+macro_rules! impl_middleware_for_tuple {
+    ($m:ident) => {
+        impl<S, $m> Middleware<S> for ($m,)
+        where
+            $m: Middleware<S>,
+        {
+            type Service = $m::Service;
+
+            #[inline]
+            fn transform(self, service: S) -> Self::Service {
+                self.0.transform(service)
+            }
+        }
+    };
+    ($m:ident, $($rest:ident),+) => {
+        impl<S, $m, $($rest),+> Middleware<S> for ($m, $($rest),+)
+        where
+            ($($rest,)+): Middleware<S>,
+            $m: Middleware<<($($rest,)+) as Middleware<S>>::Service>,
+        {
+            type Service = $m::Service;
+
+            #[inline]
+            #[allow(non_snake_case)]
+            fn transform(self, service: S) -> Self::Service {
+                let ($m, $($rest),+) = self;
+                $m.transform(($($rest,)+).transform(service))
+            }
+        }
+
+        impl_middleware_for_tuple!($($rest),+);
+    };
+}
+
+impl_middleware_for_tuple!(M1, M2, M3, M4, M5, M6, M7, M8, M9, M10, M11, M12);
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::future::{ready, Future, Ready};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use crate::middleware::Middleware;
+    use crate::service::{service_fn, Service};
+
+    type Trace = Vec<&'static str>;
+
+    /// 在请求中记录自己的名称，用于检查中间件的应用顺序。
+    struct Tag(&'static str);
+
+    struct Tagged<S> {
+        service: S,
+        name: &'static str,
+    }
+
+    impl<S> Middleware<S> for Tag {
+        type Service = Tagged<S>;
+
+        fn transform(self, service: S) -> Self::Service {
+            Tagged {
+                service,
+                name: self.0,
+            }
+        }
+    }
+
+    impl<S> Service<Trace> for Tagged<S>
+    where
+        S: Service<Trace>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future<'f> = S::Future<'f>
+        where
+            Self: 'f;
+
+        fn call(&self, mut req: Trace) -> Self::Future<'_> {
+            req.push(self.name);
+            self.service.call(req)
+        }
+    }
+
+    fn inner() -> impl Service<Trace, Response = Trace, Error = Infallible> {
+        service_fn(|req: Trace| -> Ready<Result<Trace, Infallible>> { ready(Ok(req)) })
+    }
+
+    fn call<S>(service: S) -> Trace
+    where
+        S: Service<Trace, Response = Trace, Error = Infallible>,
+    {
+        let fut = pin!(service.call(Vec::new()));
+        match fut.poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(Ok(res)) => res,
+            Poll::Pending => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn tuple() {
+        // 靠前的中间件位于外层，先处理请求。
+        let stack = (Tag("a"), Tag("b"), Tag("c"));
+        assert_eq!(call(stack.transform(inner())), ["a", "b", "c"]);
+
+        let nested = Tag("a").transform(Tag("b").transform(Tag("c").transform(inner())));
+        assert_eq!(call(nested), ["a", "b", "c"]);
+
+        let stack = (Tag("a"), (Tag("b"), Tag("c")), Tag("d"));
+        assert_eq!(call(stack.transform(inner())), ["a", "b", "c", "d"]);
+
+        assert_eq!(call((Tag("a"),).transform(inner())), ["a"]);
+        assert_eq!(call(().transform(inner())), Trace::new());
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(call(Some(Tag("a")).transform(inner())), ["a"]);
+        assert_eq!(call(None::<Tag>.transform(inner())), Trace::new());
+
+        let stack = (Some(Tag("a")), None::<Tag>, Some(Tag("c")));
+        assert_eq!(call(stack.transform(inner())), ["a", "c"]);
+    }
+}
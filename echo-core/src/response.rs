@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use http::{header, HeaderMap, HeaderValue, StatusCode};
 
-use crate::body::{Body, BodyExt, BoxBody, Bytes, MapErr, StreamBody};
+use crate::body::{Body, BodyExt, BoxBody, Bytes, DataTooLarge, MapErr, StreamBody};
 use crate::BoxError;
 
 pub use crate::Response;
@@ -11,6 +11,79 @@ pub trait IntoResponse {
     fn into_response(self) -> Response;
 }
 
+/// 可以追加到响应上的部分，例如响应头。
+///
+/// 实现了该特征的类型可以与响应体组成元组作为响应返回，例如 `(HeaderMap, T)`。
+pub trait IntoResponseParts {
+    fn into_response_parts(self, res: &mut Response);
+}
+
+impl IntoResponseParts for HeaderMap {
+    fn into_response_parts(self, res: &mut Response) {
+        res.headers_mut().extend(self);
+    }
+}
+
+impl<P> IntoResponseParts for Option<P>
+where
+    P: IntoResponseParts,
+{
+    fn into_response_parts(self, res: &mut Response) {
+        if let Some(parts) = self {
+            parts.into_response_parts(res);
+        }
+    }
+}
+
+macro_rules! impl_into_response_parts {
+    ($($ty:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($ty,)*> IntoResponseParts for ($($ty,)*)
+        where
+            $($ty: IntoResponseParts,)*
+        {
+            fn into_response_parts(self, res: &mut Response) {
+                let ($($ty,)*) = self;
+                $($ty.into_response_parts(res);)*
+            }
+        }
+    };
+}
+
+impl_into_response_parts!(P1);
+impl_into_response_parts!(P1, P2);
+impl_into_response_parts!(P1, P2, P3);
+impl_into_response_parts!(P1, P2, P3, P4);
+impl_into_response_parts!(P1, P2, P3, P4, P5);
+impl_into_response_parts!(P1, P2, P3, P4, P5, P6);
+
+/// 将处理函数的返回值转换为 `Result<Response, BoxError>`。
+///
+/// 处理函数既可以直接返回实现了 [`IntoResponse`] 的类型，也可以返回
+/// `Result<impl IntoResponse, impl Into<BoxError>>`。
+pub trait IntoResponseResult {
+    fn into_response_result(self) -> Result<Response, BoxError>;
+}
+
+impl<T> IntoResponseResult for T
+where
+    T: IntoResponse,
+{
+    fn into_response_result(self) -> Result<Response, BoxError> {
+        Ok(self.into_response())
+    }
+}
+
+impl<T, E> IntoResponseResult for Result<T, E>
+where
+    T: IntoResponse,
+    E: Into<BoxError>,
+{
+    fn into_response_result(self) -> Result<Response, BoxError> {
+        self.map(IntoResponse::into_response).map_err(Into::into)
+    }
+}
+
 impl IntoResponse for () {
     fn into_response(self) -> Response {
         Response::new(self.boxed())
@@ -125,6 +198,12 @@ impl IntoResponse for StatusCode {
     }
 }
 
+impl IntoResponse for DataTooLarge {
+    fn into_response(self) -> Response {
+        StatusCode::PAYLOAD_TOO_LARGE.into_response()
+    }
+}
+
 impl IntoResponse for HeaderMap {
     fn into_response(self) -> Response {
         let mut res = Response::new(().boxed());
@@ -153,25 +232,27 @@ where
     }
 }
 
-impl<T> IntoResponse for (HeaderMap, T)
+impl<P, T> IntoResponse for (P, T)
 where
+    P: IntoResponseParts,
     T: IntoResponse,
 {
     fn into_response(self) -> Response {
         let mut res = self.1.into_response();
-        res.headers_mut().extend(self.0);
+        self.0.into_response_parts(&mut res);
         res
     }
 }
 
-impl<T> IntoResponse for (StatusCode, HeaderMap, T)
+impl<P, T> IntoResponse for (StatusCode, P, T)
 where
+    P: IntoResponseParts,
     T: IntoResponse,
 {
     fn into_response(self) -> Response {
         let mut res = self.2.into_response();
         *res.status_mut() = self.0;
-        res.headers_mut().extend(self.1);
+        self.1.into_response_parts(&mut res);
         res
     }
 }
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::Service;
+
+/// 两个服务之一，两者的响应和错误类型必须相同。
+#[derive(Clone, Copy)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A, B, Req> Service<Req> for Either<A, B>
+where
+    A: Service<Req>,
+    B: Service<Req, Response = A::Response, Error = A::Error>,
+{
+    type Response = A::Response;
+    type Error = A::Error;
+    type Future<'f> = EitherFuture<A::Future<'f>, B::Future<'f>>
+    where
+        Self: 'f;
+
+    #[inline]
+    fn call(&self, req: Req) -> Self::Future<'_> {
+        match self {
+            Either::Left(service) => EitherFuture::Left {
+                fut: service.call(req),
+            },
+            Either::Right(service) => EitherFuture::Right {
+                fut: service.call(req),
+            },
+        }
+    }
+}
+
+impl<A, B> fmt::Debug for Either<A, B>
+where
+    A: fmt::Debug,
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Either::Left(service) => f.debug_tuple("Left").field(service).finish(),
+            Either::Right(service) => f.debug_tuple("Right").field(service).finish(),
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = EitherFutureProj]
+    pub enum EitherFuture<A, B> {
+        Left { #[pin] fut: A },
+        Right { #[pin] fut: B },
+    }
+}
+
+impl<A, B> Future for EitherFuture<A, B>
+where
+    A: Future,
+    B: Future<Output = A::Output>,
+{
+    type Output = A::Output;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            EitherFutureProj::Left { fut } => fut.poll(cx),
+            EitherFutureProj::Right { fut } => fut.poll(cx),
+        }
+    }
+}
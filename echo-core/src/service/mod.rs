@@ -1,6 +1,7 @@
 mod and_then;
 mod boxed;
 mod boxed_local;
+mod either;
 mod ext;
 mod map;
 mod map_err;
@@ -14,6 +15,7 @@ pub mod future {
     pub use super::and_then::AndThenFuture;
     pub use super::boxed::BoxFuture;
     pub use super::boxed_local::LocalBoxFuture;
+    pub use super::either::EitherFuture;
     pub use super::map::MapFuture;
     pub use super::map_err::MapErrFuture;
     pub use super::map_ok::MapOkFuture;
@@ -24,6 +26,7 @@ pub mod future {
 pub use and_then::AndThen;
 pub use boxed::{ArcService, BoxCloneService, BoxService};
 pub use boxed_local::{LocalBoxCloneService, LocalBoxService, RcService};
+pub use either::Either;
 pub use ext::ServiceExt;
 pub use map::Map;
 pub use map_err::MapErr;
@@ -45,7 +48,7 @@ pub trait Service<Req> {
     fn call(&self, req: Req) -> Self::Future<'_>;
 }
 
-impl<'a, S, Req> Service<Req> for &'a mut S
+impl<S, Req> Service<Req> for &mut S
 where
     S: Service<Req> + ?Sized,
 {
@@ -59,7 +62,7 @@ where
     }
 }
 
-impl<'a, S, Req> Service<Req> for &'a S
+impl<S, Req> Service<Req> for &S
 where
     S: Service<Req> + ?Sized,
 {
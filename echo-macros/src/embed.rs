@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use sha2::{Digest, Sha256};
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Ident, LitBool, LitStr, Token};
+
+pub fn embed_dir(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as Args);
+
+    match EmbedDir::new(args) {
+        Ok(dir) => dir.into_token_stream().into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+pub struct Args {
+    path: LitStr,
+    gzip: bool,
+    br: bool,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self {
+            path: input.parse()?,
+            gzip: false,
+            br: false,
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitBool = input.parse()?;
+            match name.to_string().as_str() {
+                "gzip" => args.gzip = value.value,
+                "br" => args.br = value.value,
+                _ => return Err(Error::new_spanned(name, "Unknown attribute.")),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+pub struct EmbedDir {
+    root: PathBuf,
+    files: Vec<File>,
+}
+
+struct File {
+    path: String,
+    abs_path: PathBuf,
+    etag: String,
+    mime: String,
+    gzip: Option<Vec<u8>>,
+    br: Option<Vec<u8>>,
+}
+
+impl EmbedDir {
+    pub fn new(args: Args) -> syn::Result<Self> {
+        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set"))?;
+        let root = Path::new(&manifest_dir).join(args.path.value());
+        if !root.is_dir() {
+            return Err(Error::new_spanned(
+                &args.path,
+                format!("`{}` is not a directory", root.display()),
+            ));
+        }
+
+        let mut paths = Vec::new();
+        walk(&root, &mut HashSet::new(), &mut paths)
+            .map_err(|e| Error::new_spanned(&args.path, e))?;
+
+        let mut files = Vec::with_capacity(paths.len());
+        for abs_path in paths {
+            let path = abs_path
+                .strip_prefix(&root)
+                .unwrap()
+                .components()
+                .map(|c| c.as_os_str().to_str())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    Error::new_spanned(
+                        &args.path,
+                        format!("`{}` is not valid UTF-8", abs_path.display()),
+                    )
+                })?
+                .join("/");
+
+            let contents = std::fs::read(&abs_path).map_err(|e| {
+                Error::new_spanned(
+                    &args.path,
+                    format!("failed to read `{}`: {e}", abs_path.display()),
+                )
+            })?;
+
+            let hash = Sha256::digest(&contents);
+            let etag = hash[..16].iter().fold(String::from("\""), |mut etag, b| {
+                etag.push_str(&format!("{b:02x}"));
+                etag
+            }) + "\"";
+
+            files.push(File {
+                mime: mime_guess::from_path(&path)
+                    .first_or_octet_stream()
+                    .to_string(),
+                gzip: args.gzip.then(|| gzip(&contents)).flatten(),
+                br: args.br.then(|| brotli(&contents)).flatten(),
+                path,
+                abs_path,
+                etag,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self { root, files })
+    }
+}
+
+impl ToTokens for EmbedDir {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let root = self.root.to_string_lossy();
+        let files = self.files.iter().map(|file| {
+            let File {
+                path,
+                abs_path,
+                etag,
+                mime,
+                gzip,
+                br,
+            } = file;
+            let abs_path = abs_path.to_string_lossy();
+            let gzip = option_bytes(gzip);
+            let br = option_bytes(br);
+            quote! {
+                ::echo::fs::EmbeddedFile {
+                    path: #path,
+                    contents: ::core::include_bytes!(#abs_path),
+                    etag: #etag,
+                    mime: #mime,
+                    gzip: #gzip,
+                    br: #br,
+                }
+            }
+        });
+
+        tokens.extend(quote! {{
+            #[cfg(debug_assertions)]
+            let dir = ::echo::fs::EmbeddedDir::from_disk(#root);
+            #[cfg(not(debug_assertions))]
+            let dir = ::echo::fs::EmbeddedDir::new(&[#(#files),*]);
+            dir
+        }});
+    }
+}
+
+fn option_bytes(bytes: &Option<Vec<u8>>) -> TokenStream2 {
+    match bytes {
+        Some(bytes) => {
+            let bytes = Literal::byte_string(bytes);
+            quote!(::core::option::Option::Some(#bytes))
+        }
+        None => quote!(::core::option::Option::None),
+    }
+}
+
+/// 递归收集目录中的文件，跟随符号链接。
+///
+/// `ancestors` 是当前目录及其祖先目录的真实路径，指向祖先目录的符号链接会被跳过，以免无限递归。
+fn walk(
+    dir: &Path,
+    ancestors: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    let real = dir.canonicalize()?;
+    if !ancestors.insert(real.clone()) {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk(&path, ancestors, paths)?;
+        } else if path.is_file() {
+            paths.push(path);
+        }
+    }
+    ancestors.remove(&real);
+    Ok(())
+}
+
+/// 压缩后不比原始内容小时返回 `None`。
+fn gzip(contents: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(contents).ok()?;
+    let compressed = encoder.finish().ok()?;
+    (compressed.len() < contents.len()).then_some(compressed)
+}
+
+fn brotli(contents: &[u8]) -> Option<Vec<u8>> {
+    let mut compressed = Vec::new();
+    {
+        let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        encoder.write_all(contents).ok()?;
+    }
+    (compressed.len() < contents.len()).then_some(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::walk;
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_symlink_cycle() {
+        let root = std::env::temp_dir().join(format!("echo-embed-walk-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("a")).unwrap();
+        std::fs::write(root.join("a/file.txt"), "file").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("a/parent")).unwrap();
+        // 指向非祖先目录的符号链接仍然会被跟随。
+        std::os::unix::fs::symlink(root.join("a"), root.join("b")).unwrap();
+
+        let mut paths = Vec::new();
+        walk(&root, &mut HashSet::new(), &mut paths).unwrap();
+        paths.sort();
+        assert_eq!(paths, [root.join("a/file.txt"), root.join("b/file.txt")]);
+    }
+}
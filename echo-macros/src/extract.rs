@@ -0,0 +1,324 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Attribute, Error, FnArg, Ident, ItemFn, Lit, LitStr, Meta, NestedMeta, Pat, Type};
+
+/// 处理函数参数的提取方式。
+enum Extractor {
+    /// 通过 `FromRequestParts` 提取。
+    Parts,
+    /// 通过 `FromRequest` 提取，只能是最后一个参数。
+    Request,
+    /// `#[path]`：按名称提取路径参数。
+    Path(LitStr),
+    /// `#[header("name")]`：按名称提取请求头。
+    Header(LitStr),
+    /// `#[state]`：从请求扩展中提取共享状态。
+    State,
+}
+
+struct Arg {
+    ident: Ident,
+    ty: Type,
+    extractor: Extractor,
+}
+
+pub struct Extract {
+    args: Vec<Arg>,
+}
+
+impl Extract {
+    /// 解析处理函数的参数，并移除宏识别的参数属性。
+    pub fn new(item_fn: &mut ItemFn, path: &LitStr) -> syn::Result<Self> {
+        let params = path_params(&path.value());
+
+        let len = item_fn.sig.inputs.len();
+        let mut args = Vec::with_capacity(len);
+
+        for (i, input) in item_fn.sig.inputs.iter_mut().enumerate() {
+            let pat_type = match input {
+                FnArg::Typed(pat_type) => pat_type,
+                FnArg::Receiver(receiver) => {
+                    return Err(Error::new_spanned(
+                        receiver,
+                        "handler functions can't take `self`",
+                    ));
+                }
+            };
+
+            let mut extractor = None;
+            let mut attrs = Vec::with_capacity(pat_type.attrs.len());
+
+            for attr in pat_type.attrs.drain(..) {
+                match parse_extractor(&attr, &pat_type.pat)? {
+                    Some(e) => {
+                        if extractor.is_some() {
+                            return Err(Error::new_spanned(
+                                attr,
+                                "multiple extractor attributes specified! Should be only one!",
+                            ));
+                        }
+                        if let Extractor::Path(name) = &e {
+                            if !params.contains(&name.value()) {
+                                return Err(Error::new_spanned(
+                                    name,
+                                    format!(
+                                        "path param `{}` not found in route path `{}`",
+                                        name.value(),
+                                        path.value()
+                                    ),
+                                ));
+                            }
+                        }
+                        extractor = Some(e);
+                    }
+                    None => attrs.push(attr),
+                }
+            }
+
+            pat_type.attrs = attrs;
+
+            let extractor = extractor.unwrap_or(if i + 1 == len {
+                Extractor::Request
+            } else {
+                Extractor::Parts
+            });
+
+            args.push(Arg {
+                ident: format_ident!("__echo_arg{}", i),
+                ty: (*pat_type.ty).clone(),
+                extractor,
+            });
+        }
+
+        Ok(Self { args })
+    }
+
+    /// 生成将请求转换为处理函数参数并调用处理函数的异步函数。
+    pub fn handler(&self, name: &Ident, handler: &Ident) -> TokenStream2 {
+        let by_name = self
+            .args
+            .iter()
+            .filter_map(Self::extract_by_name);
+        let from_parts = self
+            .args
+            .iter()
+            .filter_map(Self::extract_from_parts);
+        let from_request = self
+            .args
+            .iter()
+            .filter_map(Self::extract_from_request);
+
+        let has_parts = self
+            .args
+            .iter()
+            .any(|arg| matches!(arg.extractor, Extractor::Parts | Extractor::State));
+        let has_request = self
+            .args
+            .iter()
+            .any(|arg| matches!(arg.extractor, Extractor::Request));
+
+        let split = match (has_parts, has_request) {
+            (_, true) => quote! {
+                #[allow(unused_mut)]
+                let (mut __echo_parts, __echo_body) = __echo_req.into_parts();
+            },
+            (true, false) => quote! {
+                let (mut __echo_parts, _) = __echo_req.into_parts();
+            },
+            (false, false) => quote! {},
+        };
+
+        let idents = self.args.iter().map(|arg| &arg.ident);
+
+        quote! {
+            async fn #handler(
+                __echo_req: ::echo::Request,
+            ) -> ::std::result::Result<::echo::Response, ::echo::BoxError> {
+                #(#by_name)*
+                #split
+                #(#from_parts)*
+                #(#from_request)*
+                ::echo::response::IntoResponseResult::into_response_result(
+                    #name(#(#idents),*).await
+                )
+            }
+        }
+    }
+
+    fn extract_by_name(arg: &Arg) -> Option<TokenStream2> {
+        let Arg { ident, ty, .. } = arg;
+        let span = ty.span();
+        let expr = match &arg.extractor {
+            Extractor::Path(name) => or_reject(quote_spanned! {span=>
+                ::echo::extract::path::<#ty>(&__echo_req, #name)
+            }),
+            Extractor::Header(name) => or_reject(quote_spanned! {span=>
+                ::echo::extract::header::<#ty, _>(&__echo_req, #name)
+            }),
+            _ => return None,
+        };
+        Some(quote! {
+            let #ident = #expr;
+        })
+    }
+
+    fn extract_from_parts(arg: &Arg) -> Option<TokenStream2> {
+        let Arg { ident, ty, .. } = arg;
+        let span = ty.span();
+        let stmt = match &arg.extractor {
+            Extractor::Parts => {
+                let value = or_reject(quote_spanned! {span=>
+                    <#ty as ::echo::extract::FromRequestParts>::from_request_parts(
+                        &mut __echo_parts,
+                    )
+                    .await
+                });
+                quote! { let #ident = #value; }
+            }
+            Extractor::State => {
+                let value = or_reject(quote_spanned! {span=>
+                    <::echo::extract::Extension<#ty> as ::echo::extract::FromRequestParts>::from_request_parts(
+                        &mut __echo_parts,
+                    )
+                    .await
+                });
+                quote! { let ::echo::extract::Extension(#ident) = #value; }
+            }
+            _ => return None,
+        };
+        Some(stmt)
+    }
+
+    fn extract_from_request(arg: &Arg) -> Option<TokenStream2> {
+        let Arg { ident, ty, .. } = arg;
+        let span = ty.span();
+        match &arg.extractor {
+            Extractor::Request => {
+                let value = or_reject(quote_spanned! {span=>
+                    <#ty as ::echo::extract::FromRequest>::from_request(
+                        ::echo::Request::from_parts(__echo_parts, __echo_body),
+                    )
+                    .await
+                });
+                Some(quote! { let #ident = #value; })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 提取失败时与 `echo::handler::handler` 一样通过 `rejection` 返回，请求体过大时响应 413。
+fn or_reject(expr: TokenStream2) -> TokenStream2 {
+    quote! {
+        match #expr {
+            ::std::result::Result::Ok(value) => value,
+            ::std::result::Result::Err(e) => {
+                return ::echo::handler::rejection(::std::convert::Into::into(e));
+            }
+        }
+    }
+}
+
+/// 移除参数上宏识别的属性，用于在出错时原样输出函数。
+pub fn strip_attrs(item_fn: &mut ItemFn) {
+    for input in item_fn.sig.inputs.iter_mut() {
+        if let FnArg::Typed(pat_type) = input {
+            pat_type.attrs.retain(|attr| {
+                !["path", "header", "state"]
+                    .iter()
+                    .any(|name| attr.path.is_ident(name))
+            });
+        }
+    }
+}
+
+fn parse_extractor(attr: &Attribute, pat: &Pat) -> syn::Result<Option<Extractor>> {
+    let kind = if attr.path.is_ident("path") {
+        "path"
+    } else if attr.path.is_ident("header") {
+        "header"
+    } else if attr.path.is_ident("state") {
+        "state"
+    } else {
+        return Ok(None);
+    };
+
+    let name = match attr.parse_meta()? {
+        Meta::Path(_) => None,
+        Meta::List(list) if kind != "state" && list.nested.len() == 1 => {
+            match list.nested.first() {
+                Some(NestedMeta::Lit(Lit::Str(lit))) => Some(lit.clone()),
+                _ => {
+                    return Err(Error::new_spanned(
+                        list.nested,
+                        "expected a literal string",
+                    ))
+                }
+            }
+        }
+        meta => {
+            let expected = match kind {
+                "state" => "expected `#[state]`",
+                "path" => r#"expected `#[path]` or `#[path("<name>")]`"#,
+                _ => r#"expected `#[header("<name>")]`"#,
+            };
+            return Err(Error::new_spanned(meta, expected));
+        }
+    };
+
+    let extractor = match kind {
+        "path" => Extractor::Path(match name {
+            Some(name) => name,
+            None => infer_name(attr, pat)?,
+        }),
+        "header" => {
+            let name =
+                name.ok_or_else(|| Error::new_spanned(attr, r#"expected `#[header("<name>")]`"#))?;
+            if !is_header_name(&name.value()) {
+                return Err(Error::new_spanned(
+                    &name,
+                    format!("invalid header name `{}`", name.value()),
+                ));
+            }
+            Extractor::Header(name)
+        }
+        _ => Extractor::State,
+    };
+
+    Ok(Some(extractor))
+}
+
+fn infer_name(attr: &Attribute, pat: &Pat) -> syn::Result<LitStr> {
+    match pat {
+        Pat::Ident(pat_ident) => {
+            let name = pat_ident.ident.to_string();
+            let name = name.strip_prefix("r#").unwrap_or(&name);
+            Ok(LitStr::new(name, pat_ident.ident.span()))
+        }
+        _ => Err(Error::new_spanned(
+            attr,
+            r#"path param name can't be inferred from a pattern, use `#[path("<name>")]`"#,
+        )),
+    }
+}
+
+fn path_params(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(|segment| {
+            segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('*'))
+        })
+        .filter(|name| !name.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// 请求头名称必须是 RFC 9110 中的 token。
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
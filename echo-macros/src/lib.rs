@@ -1,9 +1,19 @@
+#[cfg(feature = "embed")]
+mod embed;
+mod extract;
 mod route;
 
 use proc_macro::TokenStream;
 
 /// 将异步函数包装为路由，并允许设置多个HTTP访问方法。
 ///
+/// 函数的参数会自动从请求中提取：
+///
+/// - 未标注属性的参数通过 `FromRequestParts` 提取，最后一个参数通过 `FromRequest` 提取；
+/// - `#[path]` 或 `#[path("<name>")]` 按名称提取路径参数，省略名称时使用参数名；
+/// - `#[header("<name>")]` 按名称提取请求头，名称不合法时编译报错；
+/// - `#[state]` 从请求扩展中提取共享状态。
+///
 /// # 例子
 ///
 /// ```
@@ -14,7 +24,40 @@ use proc_macro::TokenStream;
 ///     Ok("")
 /// }
 /// ```
+///
+/// ```
+/// # use echo::extract::Json;
+/// #[echo::route("/users/:id", method = "PUT")]
+/// async fn update(
+///     #[path] id: u32,
+///     #[header("user-agent")] agent: String,
+///     Json(name): Json<String>,
+/// ) -> String {
+///     format!("{id} {agent} {name}")
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn route(args: TokenStream, input: TokenStream) -> TokenStream {
     route::route(args, input)
 }
+
+/// 在编译时将目录嵌入二进制文件，返回可以配合 `Router::scope` 使用的 `echo::fs::EmbeddedDir`。
+///
+/// 路径相对于当前 crate 的 `Cargo.toml` 所在目录。每个文件的 ETag 和 MIME 类型在编译时计算，
+/// 可以通过 `gzip = true` 和 `br = true` 同时嵌入预先压缩的内容。
+///
+/// 调试构建（`debug_assertions`）时不会嵌入文件，而是在运行时从磁盘读取，修改文件后无需重新编译。
+/// 发布构建时新增的文件不会触发重新编译，需要手动清理构建缓存。
+///
+/// # 例子
+///
+/// ```
+/// use echo::route::Router;
+///
+/// let app = Router::new().scope("/assets", echo::fs::embed_dir!("src", gzip = true, br = true));
+/// ```
+#[cfg(feature = "embed")]
+#[proc_macro]
+pub fn embed_dir(input: TokenStream) -> TokenStream {
+    embed::embed_dir(input)
+}
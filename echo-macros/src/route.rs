@@ -5,6 +5,8 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
 use syn::{Attribute, AttributeArgs, Error, Ident, ItemFn, Lit, LitStr, NestedMeta, Visibility};
 
+use crate::extract::Extract;
+
 pub fn route(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as syn::AttributeArgs);
 
@@ -13,9 +15,13 @@ pub fn route(args: TokenStream, input: TokenStream) -> TokenStream {
         Err(err) => return input_and_compile_error(input, err),
     };
 
-    match Route::new(args, item_fn) {
+    match Route::new(args, item_fn.clone()) {
         Ok(route) => route.into_token_stream().into(),
-        Err(err) => input_and_compile_error(input, err),
+        Err(err) => {
+            let mut item_fn = item_fn;
+            crate::extract::strip_attrs(&mut item_fn);
+            input_and_compile_error(item_fn.into_token_stream().into(), err)
+        }
     }
 }
 
@@ -47,7 +53,7 @@ impl Args {
                         if !methods.insert(Method::try_from(lit)?) {
                             return Err(Error::new_spanned(
                                 &nv.lit,
-                                &format!("HTTP method defined more than once: `{}`", lit.value()),
+                                format!("HTTP method defined more than once: `{}`", lit.value()),
                             ));
                         }
                     } else {
@@ -67,7 +73,7 @@ impl Args {
             path: path.ok_or_else(|| {
                 Error::new(
                     Span::call_site(),
-                    format!(r#"invalid route definition, expected #[route("<path>")]"#,),
+                    r#"invalid route definition, expected #[route("<path>")]"#.to_string(),
                 )
             })?,
             methods,
@@ -81,10 +87,11 @@ pub struct Route {
     name: Ident,
     args: Args,
     docs: Vec<Attribute>,
+    extract: Extract,
 }
 
 impl Route {
-    pub fn new(args: AttributeArgs, item_fn: ItemFn) -> syn::Result<Self> {
+    pub fn new(args: AttributeArgs, mut item_fn: ItemFn) -> syn::Result<Self> {
         let vis = item_fn.vis.clone();
         let name = item_fn.sig.ident.clone();
 
@@ -96,6 +103,7 @@ impl Route {
             .collect();
 
         let args = Args::new(args)?;
+        let extract = Extract::new(&mut item_fn, &args.path)?;
 
         Ok(Self {
             item_fn,
@@ -103,6 +111,7 @@ impl Route {
             name,
             args,
             docs,
+            extract,
         })
     }
 }
@@ -115,19 +124,19 @@ impl ToTokens for Route {
             name,
             args,
             docs,
+            extract,
         } = self;
 
         let Args { path, methods } = args;
 
         let methods = methods.iter();
 
-        let service = quote! {{
-            ::echo::service::service_fn(#name)
-        }};
+        let handler = Ident::new("__echo_handler", Span::call_site());
+        let handler_fn = extract.handler(name, &handler);
+
         let arc_service = quote! {{
-            let service = ::echo::service::ServiceExt::map_ok(#service, ::echo::response::IntoResponse::into_response);
-            let service = ::echo::service::ServiceExt::map_err(service, ::std::convert::Into::into);
-            <::echo::service::MapErr<_, _> as ::echo::service::ServiceExt<_>>::boxed_arc(service)
+            let service = ::echo::service::service_fn(#handler);
+            <::echo::service::ServiceFn<_> as ::echo::service::ServiceExt<_>>::boxed_arc(service)
         }};
         let route = quote! {{
             let service = ::echo::route::any(#arc_service)
@@ -158,6 +167,7 @@ impl ToTokens for Route {
             impl ::std::convert::Into<#route_ty> for #name {
                 fn into(self) -> #route_ty {
                     #item_fn
+                    #handler_fn
                     #route
                 }
             }
@@ -181,7 +191,7 @@ impl TryFrom<&LitStr> for Method {
 
     fn try_from(value: &LitStr) -> Result<Self, Self::Error> {
         let method = value.value();
-        if method.len() == 0 {
+        if method.is_empty() {
             Err(Error::new_spanned(value, "invalid HTTP method"))
         } else {
             Ok(Method(method))
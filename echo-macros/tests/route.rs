@@ -0,0 +1,120 @@
+use echo::body::{BodyExt, BoxBody, Bytes};
+use echo::extract::{Json, Path};
+use echo::http::{Method, StatusCode};
+use echo::middleware::DEFAULT_BODY_LIMIT;
+use echo::route::Router;
+use echo::service::Service;
+use echo::{Request, Response};
+
+#[derive(Clone)]
+struct Prefix(&'static str);
+
+#[echo::route("/users/:id/posts/:slug", method = "POST")]
+async fn update(
+    #[path] id: u32,
+    #[path("slug")] post: String,
+    #[header("x-name")] name: String,
+    #[state] prefix: Prefix,
+    Json(body): Json<String>,
+) -> String {
+    format!("{} {id} {post} {name} {body}", prefix.0)
+}
+
+fn router() -> Router {
+    Router::new().mount(update)
+}
+
+fn request(uri: &str) -> echo::http::request::Builder {
+    Request::builder().method(Method::POST).uri(uri)
+}
+
+fn json(builder: echo::http::request::Builder, body: &'static str) -> Request {
+    let mut req = builder
+        .header("content-type", "application/json")
+        .body(BoxBody::new(body))
+        .unwrap();
+    req.extensions_mut().insert(Prefix("post"));
+    req
+}
+
+async fn body(res: Response) -> Bytes {
+    res.into_body().collect().await.unwrap().to_bytes()
+}
+
+#[tokio::test]
+async fn extract() {
+    let req = json(
+        request("/users/7/posts/hello").header("x-name", "echo"),
+        r#""text""#,
+    );
+    let res = router().call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(body(res).await, "post 7 hello echo text");
+}
+
+#[tokio::test]
+async fn path() {
+    let req = json(
+        request("/users/abc/posts/hello").header("x-name", "echo"),
+        r#""""#,
+    );
+    let e = router().call(req).await.unwrap_err();
+    assert!(e.is::<echo::extract::ExtractPathError>());
+}
+
+#[tokio::test]
+async fn header() {
+    let req = json(request("/users/7/posts/hello"), r#""""#);
+    let e = router().call(req).await.unwrap_err();
+    assert!(e.is::<echo::extract::ExtractHeaderError>());
+}
+
+#[tokio::test]
+async fn state() {
+    let mut req = json(
+        request("/users/7/posts/hello").header("x-name", "echo"),
+        r#""""#,
+    );
+    req.extensions_mut().remove::<Prefix>();
+    let e = router().call(req).await.unwrap_err();
+    assert!(e.is::<echo::extract::ExtractExtensionError>());
+}
+
+#[echo::route("/files/:name")]
+async fn file(#[path] name: String, Path(decoded): Path<String>) -> String {
+    format!("{name}|{decoded}")
+}
+
+#[tokio::test]
+async fn path_decode() {
+    // `#[path]` 与 `Path<T>` 一样会进行百分号解码。
+    let req = Request::builder()
+        .uri("/files/a%20b")
+        .body(BoxBody::default())
+        .unwrap();
+    let res = Router::new().mount(file).call(req).await.unwrap();
+    assert_eq!(body(res).await, "a b|a b");
+}
+
+#[echo::route("/upload", method = "POST")]
+async fn upload(body: Bytes) -> String {
+    body.len().to_string()
+}
+
+#[tokio::test]
+async fn too_large() {
+    let req = request("/upload")
+        .body(BoxBody::new(vec![0; DEFAULT_BODY_LIMIT + 1]))
+        .unwrap();
+    let res = Router::new().mount(upload).call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let req = json(
+        request("/users/7/posts/hello").header("x-name", "echo"),
+        r#""text""#,
+    );
+    let (parts, _) = req.into_parts();
+    let req = Request::from_parts(parts, BoxBody::new(vec![b' '; DEFAULT_BODY_LIMIT + 1]));
+    let res = router().call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,6 @@
+#![allow(unused_variables)]
+
+#[echo::route("/")]
+async fn handler(#[header("user agent")] agent: String) {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#![allow(unused_variables)]
+
+struct NotExtractable;
+
+#[echo::route("/")]
+async fn handler(_: NotExtractable, _: echo::Request) {}
+
+fn main() {}
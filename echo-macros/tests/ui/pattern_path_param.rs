@@ -0,0 +1,6 @@
+#![allow(unused_variables)]
+
+#[echo::route("/users/:id")]
+async fn handler(#[path] (id,): (String,)) {}
+
+fn main() {}
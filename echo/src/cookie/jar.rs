@@ -0,0 +1,83 @@
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+
+use echo_core::http::request::Parts;
+use echo_core::http::HeaderMap;
+use echo_core::response::{IntoResponse, IntoResponseParts, Response};
+
+use super::Cookie;
+use crate::extract::FromRequestParts;
+
+/// 未签名、未加密的 Cookie 集合。
+///
+/// 作为响应的一部分返回时，通过 [`add`](CookieJar::add) 和 [`remove`](CookieJar::remove)
+/// 修改过的 Cookie 会被写入 `Set-Cookie` 头。
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    jar: cookie::CookieJar,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 从请求头的 `Cookie` 中解析 Cookie，无法解析的 Cookie 会被忽略。
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            jar: super::parse_cookies(headers),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cookie<'static>> {
+        self.jar.get(name)
+    }
+
+    /// 添加 Cookie，同名的 Cookie 会被替换。
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn add<C>(mut self, cookie: C) -> Self
+    where
+        C: Into<Cookie<'static>>,
+    {
+        self.jar.add(cookie);
+        self
+    }
+
+    /// 删除 Cookie。如果该 Cookie 来自请求，响应中会包含使其过期的 `Set-Cookie` 头。
+    ///
+    /// 为了让客户端正确删除，`cookie` 的 `path` 和 `domain` 需要与设置时一致。
+    #[must_use]
+    pub fn remove<C>(mut self, cookie: C) -> Self
+    where
+        C: Into<Cookie<'static>>,
+    {
+        self.jar.remove(cookie);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie<'static>> {
+        self.jar.iter()
+    }
+}
+
+impl FromRequestParts for CookieJar {
+    type Error = Infallible;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(Ok(Self::from_headers(&parts.headers)))
+    }
+}
+
+impl IntoResponseParts for CookieJar {
+    fn into_response_parts(self, res: &mut Response) {
+        super::set_cookies(&self.jar, res);
+    }
+}
+
+impl IntoResponse for CookieJar {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
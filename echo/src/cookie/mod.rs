@@ -0,0 +1,191 @@
+//! Cookie 的提取与设置。
+//!
+//! [`CookieJar`] 从请求的 `Cookie` 头中解析 Cookie，添加或删除的 Cookie 在作为响应的一部分返回时
+//! 会转换为 `Set-Cookie` 头：
+//!
+//! ```
+//! use echo::cookie::{Cookie, CookieJar, SameSite};
+//! use echo::cookie::time::Duration;
+//! use echo::response::IntoResponse;
+//!
+//! async fn login(jar: CookieJar) -> impl IntoResponse {
+//!     let cookie = Cookie::build(("token", "abc"))
+//!         .path("/")
+//!         .same_site(SameSite::Lax)
+//!         .http_only(true)
+//!         .secure(true)
+//!         .max_age(Duration::hours(1));
+//!
+//!     (jar.add(cookie), "ok")
+//! }
+//! ```
+//!
+//! [`SignedCookieJar`] 与 [`PrivateCookieJar`] 分别对 Cookie 的值进行签名和加密，
+//! 所需的 [`CookieKeys`] 需要通过请求扩展提供。
+
+mod jar;
+mod private;
+mod signed;
+
+pub use cookie::{time, Cookie, CookieBuilder, Expiration, Key, SameSite};
+pub use jar::CookieJar;
+pub use private::PrivateCookieJar;
+pub use signed::SignedCookieJar;
+
+use std::fmt;
+use std::sync::Arc;
+
+use echo_core::http::{header, HeaderMap, HeaderValue};
+use echo_core::response::Response;
+
+/// 签名和加密 Cookie 所用的密钥。
+///
+/// 新的 Cookie 总是使用当前密钥，而读取时会依次尝试当前密钥和旧密钥，
+/// 从而可以在不使已签发 Cookie 失效的情况下轮换密钥。
+///
+/// 需要通过请求扩展（例如 [`add_extension`](crate::middleware::add_extension) 中间件）提供给
+/// [`SignedCookieJar`] 和 [`PrivateCookieJar`]。
+#[derive(Clone)]
+pub struct CookieKeys {
+    keys: Arc<Vec<Key>>,
+}
+
+impl CookieKeys {
+    pub fn new(current: Key) -> Self {
+        Self {
+            keys: Arc::new(vec![current]),
+        }
+    }
+
+    /// 添加一个仅用于验证或解密的旧密钥。
+    pub fn with_previous(mut self, key: Key) -> Self {
+        Arc::make_mut(&mut self.keys).push(key);
+        self
+    }
+
+    /// 用于签名和加密新 Cookie 的密钥。
+    pub fn current(&self) -> &Key {
+        &self.keys[0]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Key> {
+        self.keys.iter()
+    }
+}
+
+impl From<Key> for CookieKeys {
+    fn from(key: Key) -> Self {
+        Self::new(key)
+    }
+}
+
+impl fmt::Debug for CookieKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieKeys")
+            .field("len", &self.keys.len())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub enum ExtractCookieError {
+    MissingKeys,
+}
+
+impl fmt::Display for ExtractCookieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractCookieError::MissingKeys => {
+                f.write_str("missing request extension `CookieKeys`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractCookieError {}
+
+fn parse_cookies(headers: &HeaderMap) -> cookie::CookieJar {
+    let mut jar = cookie::CookieJar::new();
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| Cookie::split_parse_encoded(value.to_owned()))
+        .filter_map(Result::ok)
+        .for_each(|cookie| jar.add_original(cookie));
+    jar
+}
+
+fn set_cookies(jar: &cookie::CookieJar, res: &mut Response) {
+    for cookie in jar.delta() {
+        if let Ok(value) = HeaderValue::try_from(cookie.encoded().to_string()) {
+            res.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::http::{header, HeaderMap, HeaderValue};
+    use echo_core::response::{IntoResponse, Response};
+
+    use super::{Cookie, CookieJar, CookieKeys, Key, PrivateCookieJar, SignedCookieJar};
+
+    fn request_headers(res: &Response) -> HeaderMap {
+        let cookies = res
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|v| v.to_str().unwrap().split(';').next().unwrap().to_owned())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::try_from(cookies).unwrap());
+        headers
+    }
+
+    #[test]
+    fn plain() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_static("b=2"));
+        let jar = CookieJar::from_headers(&headers)
+            .add(Cookie::new("a", "1 2"))
+            .remove(Cookie::from("b"));
+        let res = (jar, ()).into_response();
+        let values = res.headers().get_all(header::SET_COOKIE).iter().count();
+        assert_eq!(values, 2);
+
+        let jar = CookieJar::from_headers(&request_headers(&res));
+        assert_eq!(jar.get("a").unwrap().value(), "1 2");
+    }
+
+    #[test]
+    fn signed_with_rotation() {
+        let old = CookieKeys::new(Key::generate());
+        let jar = SignedCookieJar::from_headers(&HeaderMap::new(), old.clone())
+            .add(Cookie::new("a", "1"));
+        let headers = request_headers(&jar.into_response());
+
+        let rotated = CookieKeys::new(Key::generate()).with_previous(old.current().clone());
+        let jar = SignedCookieJar::from_headers(&headers, rotated);
+        assert_eq!(jar.get("a").unwrap().value(), "1");
+
+        let jar = SignedCookieJar::from_headers(&headers, CookieKeys::new(Key::generate()));
+        assert!(jar.get("a").is_none());
+    }
+
+    #[test]
+    fn private() {
+        let keys = CookieKeys::new(Key::generate());
+        let res = PrivateCookieJar::from_headers(&HeaderMap::new(), keys.clone())
+            .add(Cookie::new("a", "secret"))
+            .into_response();
+        assert!(!res.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .contains("secret"));
+
+        let jar = PrivateCookieJar::from_headers(&request_headers(&res), keys);
+        assert_eq!(jar.get("a").unwrap().value(), "secret");
+    }
+}
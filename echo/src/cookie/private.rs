@@ -0,0 +1,95 @@
+use std::future::{ready, Ready};
+
+use echo_core::http::request::Parts;
+use echo_core::http::HeaderMap;
+use echo_core::response::{IntoResponse, IntoResponseParts, Response};
+
+use super::{Cookie, CookieKeys, ExtractCookieError};
+use crate::extract::FromRequestParts;
+
+/// 值经过加密的 Cookie 集合，客户端既无法篡改也无法读取 Cookie 的值。
+///
+/// 提取时需要请求扩展中存在 [`CookieKeys`]，无法解密的 Cookie 会被忽略。
+#[derive(Debug, Clone)]
+pub struct PrivateCookieJar {
+    jar: cookie::CookieJar,
+    keys: CookieKeys,
+}
+
+impl PrivateCookieJar {
+    pub fn new(keys: CookieKeys) -> Self {
+        Self {
+            jar: Default::default(),
+            keys,
+        }
+    }
+
+    /// 从请求头的 `Cookie` 中解析 Cookie。
+    pub fn from_headers(headers: &HeaderMap, keys: CookieKeys) -> Self {
+        Self {
+            jar: super::parse_cookies(headers),
+            keys,
+        }
+    }
+
+    /// 获取解密成功的 Cookie。
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.keys
+            .iter()
+            .find_map(|key| self.jar.private(key).get(name))
+    }
+
+    /// 使用当前密钥加密并添加 Cookie。
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn add<C>(mut self, cookie: C) -> Self
+    where
+        C: Into<Cookie<'static>>,
+    {
+        self.jar.private_mut(self.keys.current()).add(cookie);
+        self
+    }
+
+    /// 删除 Cookie，参见 [`CookieJar::remove`](super::CookieJar::remove)。
+    #[must_use]
+    pub fn remove<C>(mut self, cookie: C) -> Self
+    where
+        C: Into<Cookie<'static>>,
+    {
+        self.jar.remove(cookie);
+        self
+    }
+
+    /// 遍历解密成功的 Cookie。
+    pub fn iter(&self) -> impl Iterator<Item = Cookie<'static>> + '_ {
+        self.jar.iter().filter_map(|cookie| self.get(cookie.name()))
+    }
+}
+
+impl FromRequestParts for PrivateCookieJar {
+    type Error = ExtractCookieError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(
+            parts
+                .extensions
+                .get::<CookieKeys>()
+                .cloned()
+                .map(|keys| Self::from_headers(&parts.headers, keys))
+                .ok_or(ExtractCookieError::MissingKeys),
+        )
+    }
+}
+
+impl IntoResponseParts for PrivateCookieJar {
+    fn into_response_parts(self, res: &mut Response) {
+        super::set_cookies(&self.jar, res);
+    }
+}
+
+impl IntoResponse for PrivateCookieJar {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
@@ -0,0 +1,308 @@
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+
+use echo_core::http::header::{self, HeaderName};
+use echo_core::http::request::Parts;
+use echo_core::http::HeaderMap;
+use mime::Mime;
+
+use super::FromRequestParts;
+
+/// 带有质量值（`q`）的列表项。
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityItem<T> {
+    pub value: T,
+    /// 取值范围为 `0.0..=1.0`，未指定时为 `1.0`。
+    pub quality: f32,
+}
+
+/// 解析后的 `Accept` 请求头，请求中没有该头时接受任意类型。
+#[derive(Debug, Clone, Default)]
+pub struct Accept(Vec<QualityItem<Mime>>);
+
+/// 解析后的 `Accept-Language` 请求头，请求中没有该头时接受任意语言。
+#[derive(Debug, Clone, Default)]
+pub struct AcceptLanguage(Vec<QualityItem<String>>);
+
+/// 解析后的 `Accept-Encoding` 请求头。
+///
+/// 除非被 `identity;q=0` 或 `*;q=0` 显式排除，`identity` 总是可以接受的；
+/// 请求中没有该头时只接受 `identity`。
+#[derive(Debug, Clone, Default)]
+pub struct AcceptEncoding(Vec<QualityItem<String>>);
+
+impl Accept {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self(
+            parse(headers, header::ACCEPT)
+                .filter_map(|(value, quality)| {
+                    Some(QualityItem {
+                        value: value.parse().ok()?,
+                        quality,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QualityItem<Mime>> {
+        self.0.iter()
+    }
+
+    /// 返回 `mime` 的质量值，由匹配该类型的最具体的媒体范围决定，没有匹配时为 `0.0`。
+    pub fn quality(&self, mime: &Mime) -> f32 {
+        if self.0.is_empty() {
+            return 1.0;
+        }
+        quality(&self.0, mime, media_range_specificity).unwrap_or(0.0)
+    }
+
+    /// 在 `offers` 中选择质量值最高的类型，质量值相同时选择靠前的，均不可接受时返回 `None`。
+    pub fn negotiate<'o>(&self, offers: &'o [Mime]) -> Option<&'o Mime> {
+        preferred(offers, |mime| self.quality(mime))
+    }
+}
+
+impl AcceptLanguage {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self(parse_tokens(headers, header::ACCEPT_LANGUAGE))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QualityItem<String>> {
+        self.0.iter()
+    }
+
+    /// 返回语言标签的质量值，例如 `en` 可以匹配 `en-US`。
+    pub fn quality(&self, tag: &str) -> f32 {
+        if self.0.is_empty() {
+            return 1.0;
+        }
+        quality(&self.0, tag, language_range_specificity).unwrap_or(0.0)
+    }
+
+    /// 参见 [`Accept::negotiate`]。
+    pub fn negotiate<'o>(&self, offers: &[&'o str]) -> Option<&'o str> {
+        preferred(offers, |tag| self.quality(tag)).copied()
+    }
+}
+
+impl AcceptEncoding {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self(parse_tokens(headers, header::ACCEPT_ENCODING))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QualityItem<String>> {
+        self.0.iter()
+    }
+
+    /// 返回内容编码的质量值。
+    pub fn quality(&self, encoding: &str) -> f32 {
+        let quality = quality(&self.0, encoding, |range, encoding| {
+            if range == "*" {
+                Some(0)
+            } else if range.eq_ignore_ascii_case(encoding) {
+                Some(1)
+            } else {
+                None
+            }
+        });
+        match quality {
+            Some(quality) => quality,
+            None if encoding.eq_ignore_ascii_case("identity") => 1.0,
+            None => 0.0,
+        }
+    }
+
+    /// 参见 [`Accept::negotiate`]。
+    pub fn negotiate<'o>(&self, offers: &[&'o str]) -> Option<&'o str> {
+        preferred(offers, |encoding| self.quality(encoding)).copied()
+    }
+}
+
+macro_rules! impl_from_request_parts {
+    ($($ty:ty),*) => {
+        $(
+            impl FromRequestParts for $ty {
+                type Error = Infallible;
+                type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+                fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+                    ready(Ok(Self::from_headers(&parts.headers)))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_request_parts!(Accept, AcceptLanguage, AcceptEncoding);
+
+/// 解析以逗号分隔的列表，返回去掉 `q` 参数后的值及其质量值。
+///
+/// `q` 之后的参数（`accept-ext`）会被忽略，无效的质量值会使该项被忽略。
+fn parse(headers: &HeaderMap, name: HeaderName) -> impl Iterator<Item = (&str, f32)> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let item = item.trim();
+            if item.is_empty() {
+                return None;
+            }
+
+            let mut end = item.len();
+            let mut quality = 1.0;
+            let mut offset = 0;
+            for param in item.split(';') {
+                let start = offset;
+                offset += param.len() + 1;
+                let Some((key, value)) = param.split_once('=') else {
+                    continue;
+                };
+                if key.trim().eq_ignore_ascii_case("q") {
+                    quality = parse_quality(value.trim())?;
+                    end = start.saturating_sub(1);
+                    break;
+                }
+            }
+
+            Some((item[..end].trim(), quality))
+        })
+}
+
+fn parse_tokens(headers: &HeaderMap, name: HeaderName) -> Vec<QualityItem<String>> {
+    parse(headers, name)
+        .map(|(value, quality)| QualityItem {
+            value: value.to_ascii_lowercase(),
+            quality,
+        })
+        .collect()
+}
+
+fn parse_quality(value: &str) -> Option<f32> {
+    let quality = value.parse::<f32>().ok()?;
+    (0.0..=1.0).contains(&quality).then_some(quality)
+}
+
+/// 使用与 `offer` 匹配的最具体的项的质量值，具体程度相同时使用靠前的项。
+fn quality<T, O>(
+    items: &[QualityItem<T>],
+    offer: &O,
+    specificity: impl Fn(&T, &O) -> Option<usize>,
+) -> Option<f32>
+where
+    O: ?Sized,
+{
+    let mut best: Option<(usize, f32)> = None;
+    for item in items {
+        if let Some(s) = specificity(&item.value, offer) {
+            if best.is_none_or(|(b, _)| s > b) {
+                best = Some((s, item.quality));
+            }
+        }
+    }
+    best.map(|(_, quality)| quality)
+}
+
+fn preferred<O>(offers: &[O], quality: impl Fn(&O) -> f32) -> Option<&O> {
+    let mut best: Option<(&O, f32)> = None;
+    for offer in offers {
+        let q = quality(offer);
+        if q > 0.0 && best.is_none_or(|(_, b)| q > b) {
+            best = Some((offer, q));
+        }
+    }
+    best.map(|(offer, _)| offer)
+}
+
+fn media_range_specificity(range: &Mime, mime: &Mime) -> Option<usize> {
+    if range.type_() == mime::STAR {
+        return Some(0);
+    }
+    if range.type_() != mime.type_() {
+        return None;
+    }
+    if range.subtype() == mime::STAR {
+        return Some(1);
+    }
+    if range.subtype() != mime.subtype() || range.suffix() != mime.suffix() {
+        return None;
+    }
+    let mut params = range.params().peekable();
+    if params.peek().is_none() {
+        return Some(2);
+    }
+    params
+        .all(|(name, value)| mime.get_param(name) == Some(value))
+        .then_some(3)
+}
+
+fn language_range_specificity(range: &String, tag: &str) -> Option<usize> {
+    if range == "*" {
+        return Some(0);
+    }
+    let matches = tag.len() >= range.len()
+        && tag[..range.len()].eq_ignore_ascii_case(range)
+        && matches!(tag.as_bytes().get(range.len()), None | Some(b'-'));
+    matches.then_some(range.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::http::header::{self, HeaderValue};
+    use echo_core::http::HeaderMap;
+
+    use super::{Accept, AcceptEncoding, AcceptLanguage};
+
+    fn headers(name: header::HeaderName, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn accept() {
+        let accept = Accept::from_headers(&headers(
+            header::ACCEPT,
+            "text/*;q=0.3, text/html;q=0.7, text/html;level=1, */*;q=0.5",
+        ));
+        assert_eq!(accept.quality(&"text/html;level=1".parse().unwrap()), 1.0);
+        assert_eq!(accept.quality(&"text/html".parse().unwrap()), 0.7);
+        assert_eq!(accept.quality(&"text/plain".parse().unwrap()), 0.3);
+        assert_eq!(accept.quality(&"image/jpeg".parse().unwrap()), 0.5);
+
+        let offers = [mime::TEXT_PLAIN, mime::APPLICATION_JSON, mime::TEXT_HTML];
+        assert_eq!(accept.negotiate(&offers), Some(&mime::TEXT_HTML));
+
+        let accept = Accept::from_headers(&headers(header::ACCEPT, "application/json;q=0"));
+        assert_eq!(accept.negotiate(&[mime::APPLICATION_JSON]), None);
+        assert_eq!(
+            Accept::default().negotiate(&offers),
+            Some(&mime::TEXT_PLAIN)
+        );
+    }
+
+    #[test]
+    fn accept_language() {
+        let accept = AcceptLanguage::from_headers(&headers(
+            header::ACCEPT_LANGUAGE,
+            "zh-CN, zh;q=0.9, en;q=0.8",
+        ));
+        assert_eq!(accept.negotiate(&["en-US", "zh-TW"]), Some("zh-TW"));
+        assert_eq!(accept.negotiate(&["fr", "en-GB"]), Some("en-GB"));
+        assert_eq!(accept.negotiate(&["fr", "eng"]), None);
+    }
+
+    #[test]
+    fn accept_encoding() {
+        let accept =
+            AcceptEncoding::from_headers(&headers(header::ACCEPT_ENCODING, "gzip;q=0.5, br"));
+        assert_eq!(accept.negotiate(&["gzip", "br"]), Some("br"));
+        assert_eq!(accept.negotiate(&["zstd", "identity"]), Some("identity"));
+
+        let accept = AcceptEncoding::from_headers(&headers(header::ACCEPT_ENCODING, "*;q=0"));
+        assert_eq!(accept.negotiate(&["identity"]), None);
+        assert_eq!(AcceptEncoding::default().negotiate(&["gzip"]), None);
+    }
+}
@@ -1,6 +1,30 @@
-use echo_core::body::{BodyExt, Bytes};
+use echo_core::body::{BodyExt, Bytes, DataTooLarge};
+use echo_core::http::header;
 use echo_core::{BoxError, Request};
 
+use crate::middleware::body_limit::Limit;
+
+/// 读取完整的请求体。
+///
+/// 请求体的大小受 [`body_limit`](crate::middleware::body_limit) 中间件的限制，未配置时为
+/// [`DEFAULT_BODY_LIMIT`](crate::middleware::DEFAULT_BODY_LIMIT)。超过限制时返回
+/// [`DataTooLarge`] 错误，`Content-Length` 超过限制的请求不会读取请求体。
 pub async fn bytes(req: &mut Request) -> Result<Bytes, BoxError> {
-    Ok(std::mem::take(req.body_mut()).collect().await?.to_bytes())
+    let body = std::mem::take(req.body_mut());
+
+    let limit = match Limit::from_extensions(req.extensions()) {
+        Some(limit) => limit,
+        None => return Ok(body.collect().await?.to_bytes()),
+    };
+
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if content_length.is_some_and(|len| len > limit as u64) {
+        return Err(DataTooLarge.into());
+    }
+
+    Ok(body.limit(limit).collect().await?.to_bytes())
 }
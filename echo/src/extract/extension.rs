@@ -1,5 +1,11 @@
+use std::fmt;
+use std::future::{ready, Ready};
+
+use echo_core::http::request::Parts;
 use echo_core::Request;
 
+use super::FromRequestParts;
+
 pub fn extension<T>(req: &Request) -> Option<&T>
 where
     T: Send + Sync + 'static,
@@ -13,3 +19,44 @@ where
 {
     req.extensions_mut().get_mut::<T>()
 }
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extension<T>(pub T);
+
+impl<T> FromRequestParts for Extension<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    type Error = ExtractExtensionError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(
+            parts
+                .extensions
+                .get::<T>()
+                .cloned()
+                .map(Extension)
+                .ok_or_else(|| ExtractExtensionError::MissingExtension {
+                    type_name: std::any::type_name::<T>(),
+                }),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ExtractExtensionError {
+    MissingExtension { type_name: &'static str },
+}
+
+impl fmt::Display for ExtractExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractExtensionError::MissingExtension { type_name } => {
+                write!(f, "missing request extension `{type_name}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractExtensionError {}
@@ -1,10 +1,12 @@
 use std::fmt;
 
 use echo_core::http::{header, Method};
+use echo_core::service::future::BoxFuture;
 use echo_core::{BoxError, Request};
 use serde::de::DeserializeOwned;
 
-use super::query::ExtractQueryError;
+use super::query::{ExtractQueryError, NestedQueryConfig, NestedQueryError};
+use super::FromRequest;
 
 pub async fn form<T>(req: &mut Request) -> Result<T, ExtractFormError>
 where
@@ -19,13 +21,69 @@ where
 
         let bytes = crate::extract::bytes(req)
             .await
-            .map_err(|e| ExtractFormError::FailedToReadBody(e.into()))?;
+            .map_err(ExtractFormError::FailedToReadBody)?;
 
         serde_urlencoded::from_bytes(&bytes).map_err(ExtractFormError::FailedToDeserialize)
     }
 }
 
-fn has_content_type(req: &Request, expected_content_type: &mime::Mime) -> bool {
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Form<T>(pub T);
+
+impl<T> FromRequest for Form<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    type Error = ExtractFormError;
+    type Future = BoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(mut req: Request) -> Self::Future {
+        Box::pin(async move { form(&mut req).await.map(Form) })
+    }
+}
+
+/// 与 [`form`] 相同，但支持重复键与 `filter[status]=open` 形式的嵌套结构，
+/// 解析规则见 [`nested_query`](crate::extract::nested_query)。
+pub async fn nested_form<T>(req: &mut Request) -> Result<T, ExtractFormError>
+where
+    T: DeserializeOwned,
+{
+    if req.method() == Method::GET {
+        crate::extract::nested_query(req).map_err(ExtractFormError::from_extract_query_error)
+    } else {
+        if !has_content_type(req, &mime::APPLICATION_WWW_FORM_URLENCODED) {
+            return Err(ExtractFormError::UnsupportedContentType);
+        }
+
+        let config = NestedQueryConfig::from_extensions(req.extensions());
+
+        let bytes = crate::extract::bytes(req)
+            .await
+            .map_err(ExtractFormError::FailedToReadBody)?;
+
+        config
+            .deserialize_bytes(&bytes)
+            .map_err(ExtractFormError::FailedToDeserializeNested)
+    }
+}
+
+/// 通过 [`nested_form`] 提取表单。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NestedForm<T>(pub T);
+
+impl<T> FromRequest for NestedForm<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    type Error = ExtractFormError;
+    type Future = BoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(mut req: Request) -> Self::Future {
+        Box::pin(async move { nested_form(&mut req).await.map(NestedForm) })
+    }
+}
+
+pub(crate) fn has_content_type(req: &Request, expected_content_type: &mime::Mime) -> bool {
     let content_type = if let Some(content_type) = req.headers().get(header::CONTENT_TYPE) {
         content_type
     } else {
@@ -46,12 +104,16 @@ pub enum ExtractFormError {
     UnsupportedContentType,
     FailedToReadBody(BoxError),
     FailedToDeserialize(serde_urlencoded::de::Error),
+    FailedToDeserializeNested(NestedQueryError),
 }
 
 impl ExtractFormError {
     fn from_extract_query_error(error: ExtractQueryError) -> Self {
         match error {
             ExtractQueryError::FailedToDeserialize(e) => ExtractFormError::FailedToDeserialize(e),
+            ExtractQueryError::FailedToDeserializeNested(e) => {
+                ExtractFormError::FailedToDeserializeNested(e)
+            }
         }
     }
 }
@@ -64,8 +126,18 @@ impl fmt::Display for ExtractFormError {
             ExtractFormError::FailedToDeserialize(e) => {
                 write!(f, "failed to deserialize ({e})")
             }
+            ExtractFormError::FailedToDeserializeNested(e) => {
+                write!(f, "failed to deserialize ({e})")
+            }
         }
     }
 }
 
-impl std::error::Error for ExtractFormError {}
+impl std::error::Error for ExtractFormError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExtractFormError::FailedToReadBody(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
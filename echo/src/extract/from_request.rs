@@ -0,0 +1,112 @@
+use std::convert::Infallible;
+use std::future::{ready, Future, Ready};
+
+use echo_core::body::Bytes;
+use echo_core::http::request::Parts;
+use echo_core::http::{HeaderMap, Method, Uri, Version};
+use echo_core::service::future::BoxFuture;
+use echo_core::{BoxError, Request};
+
+/// 从请求头部分提取数据，不会消耗请求体。
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be extracted from the request parts",
+    note = "only the last argument of a handler can consume the request body by implementing `FromRequest`"
+)]
+pub trait FromRequestParts: Sized {
+    type Error: Into<BoxError>;
+    type Future<'a>: Future<Output = Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_>;
+}
+
+/// 从完整的请求中提取数据，可以消耗请求体。
+///
+/// 所有实现了 [`FromRequestParts`] 的类型都自动实现了该特征。
+#[diagnostic::on_unimplemented(message = "`{Self}` cannot be extracted from the request")]
+pub trait FromRequest: Sized {
+    type Error: Into<BoxError>;
+    type Future: Future<Output = Result<Self, Self::Error>>;
+
+    fn from_request(req: Request) -> Self::Future;
+}
+
+impl<T> FromRequest for T
+where
+    T: FromRequestParts + Send + 'static,
+    for<'a> T::Future<'a>: Send,
+{
+    type Error = T::Error;
+    type Future = BoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: Request) -> Self::Future {
+        let (mut parts, _) = req.into_parts();
+        Box::pin(async move { T::from_request_parts(&mut parts).await })
+    }
+}
+
+impl<T> FromRequestParts for Option<T>
+where
+    T: FromRequestParts + Send,
+    for<'a> T::Future<'a>: Send,
+{
+    type Error = Infallible;
+    type Future<'a> = BoxFuture<'a, Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        Box::pin(async move { Ok(T::from_request_parts(parts).await.ok()) })
+    }
+}
+
+impl FromRequestParts for Method {
+    type Error = Infallible;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(Ok(parts.method.clone()))
+    }
+}
+
+impl FromRequestParts for Uri {
+    type Error = Infallible;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(Ok(parts.uri.clone()))
+    }
+}
+
+impl FromRequestParts for Version {
+    type Error = Infallible;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(Ok(parts.version))
+    }
+}
+
+impl FromRequestParts for HeaderMap {
+    type Error = Infallible;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(Ok(parts.headers.clone()))
+    }
+}
+
+impl FromRequest for Request {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: Request) -> Self::Future {
+        ready(Ok(req))
+    }
+}
+
+impl FromRequest for Bytes {
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(mut req: Request) -> Self::Future {
+        Box::pin(async move { crate::extract::bytes(&mut req).await })
+    }
+}
@@ -1,9 +1,14 @@
 use std::fmt;
 
 use echo_core::http::header;
+use echo_core::service::future::BoxFuture;
 use echo_core::{BoxError, Request};
 use serde::de::DeserializeOwned;
 
+use super::FromRequest;
+
+pub use crate::response::Json;
+
 pub async fn json<T>(req: &mut Request) -> Result<T, ExtractJsonError>
 where
     T: DeserializeOwned,
@@ -14,11 +19,23 @@ where
 
     let bytes = crate::extract::bytes(req)
         .await
-        .map_err(|e| ExtractJsonError::FailedToReadBody(e.into()))?;
+        .map_err(ExtractJsonError::FailedToReadBody)?;
 
     serde_json::from_slice(&bytes).map_err(ExtractJsonError::FailedToDeserialize)
 }
 
+impl<T> FromRequest for Json<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    type Error = ExtractJsonError;
+    type Future = BoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(mut req: Request) -> Self::Future {
+        Box::pin(async move { json(&mut req).await.map(Json) })
+    }
+}
+
 fn is_json_content_type(req: &Request) -> bool {
     let content_type = if let Some(content_type) = req.headers().get(header::CONTENT_TYPE) {
         content_type
@@ -63,4 +80,11 @@ impl fmt::Display for ExtractJsonError {
     }
 }
 
-impl std::error::Error for ExtractJsonError {}
+impl std::error::Error for ExtractJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExtractJsonError::FailedToReadBody(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
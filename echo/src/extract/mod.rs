@@ -1,21 +1,37 @@
+mod accept;
 mod bytes;
 mod extension;
 mod form;
+mod from_request;
 mod header;
 mod json;
 mod path;
 mod query;
+mod remote_addr;
 mod stream;
 
 pub use self::bytes::bytes;
-pub use extension::{extension, extension_mut};
-pub use form::{form, ExtractFormError};
+pub use accept::{Accept, AcceptEncoding, AcceptLanguage, QualityItem};
+pub use extension::{extension, extension_mut, Extension, ExtractExtensionError};
+pub use form::{form, nested_form, ExtractFormError, Form, NestedForm};
+pub use from_request::{FromRequest, FromRequestParts};
 pub use header::{header, ExtractHeaderError};
-pub use json::{json, ExtractJsonError};
-pub use path::{path, ExtractPathError};
-pub use query::{query, ExtractQueryError};
+pub use json::{json, ExtractJsonError, Json};
+pub use path::{path, path_params, ExtractPathError, Path};
+pub use query::{
+    nested_query, query, ExtractQueryError, NestedQuery, NestedQueryConfig, NestedQueryError, Query,
+};
+pub use remote_addr::{remote_addr, RemoteAddr};
 pub use stream::stream;
 
+#[cfg(feature = "csrf")]
+pub(crate) use form::has_content_type;
+
+#[cfg(feature = "typed-header")]
+mod typed_header;
+#[cfg(feature = "typed-header")]
+pub use typed_header::{typed_header, ExtractTypedHeaderError, TypedHeader};
+
 #[cfg(feature = "multipart")]
 pub mod multipart;
 #[cfg(feature = "multipart")]
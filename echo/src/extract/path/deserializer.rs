@@ -0,0 +1,614 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::ExtractPathError;
+
+/// 解码后的路径参数。
+#[derive(Debug)]
+pub(super) struct Param<'de> {
+    name: &'de str,
+    value: Cow<'de, str>,
+}
+
+/// 对路径参数进行百分号解码。
+pub(super) fn decode(params: &[(String, String)]) -> Result<Vec<Param<'_>>, ExtractPathError> {
+    params
+        .iter()
+        .map(|(name, value)| {
+            percent_encoding::percent_decode_str(value)
+                .decode_utf8()
+                .map(|value| Param {
+                    name: name.as_str(),
+                    value,
+                })
+                .map_err(|e| ExtractPathError::InvalidEncoding {
+                    name: name.clone(),
+                    source: e,
+                })
+        })
+        .collect()
+}
+
+/// 将路径参数作为整体进行反序列化。
+///
+/// 结构体和映射按名称匹配参数，元组和序列按顺序匹配参数，基本类型要求恰好一个参数。
+/// 同名参数只保留最后一个，内层路由的参数会覆盖外层路由的同名参数。
+#[derive(Debug)]
+pub(super) struct PathDeserializer<'a, 'de> {
+    params: Vec<&'a Param<'de>>,
+}
+
+impl<'a, 'de> PathDeserializer<'a, 'de> {
+    pub(super) fn new(params: &'a [Param<'de>]) -> Self {
+        let mut deduped: Vec<&Param> = Vec::with_capacity(params.len());
+        for param in params {
+            deduped.retain(|p| p.name != param.name);
+            deduped.push(param);
+        }
+        Self { params: deduped }
+    }
+
+    fn single(&self) -> Result<ValueDeserializer<'a, 'de>, ExtractPathError> {
+        match self.params[..] {
+            [param] => Ok(ValueDeserializer { param }),
+            _ => Err(ExtractPathError::WrongNumberOfParams {
+                expected: 1,
+                actual: self.params.len(),
+            }),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for PathDeserializer<'a, 'de> {
+    type Error = ExtractPathError;
+
+    forward_to_single! {
+        deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+        deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf
+        deserialize_identifier
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer {
+            params: self.params.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.params.len() != len {
+            return Err(ExtractPathError::WrongNumberOfParams {
+                expected: len,
+                actual: self.params.len(),
+            });
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(MapDeserializer {
+            params: self.params.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqDeserializer<'a, 'de> {
+    params: std::vec::IntoIter<&'a Param<'de>>,
+}
+
+impl<'a, 'de> SeqAccess<'de> for SeqDeserializer<'a, 'de> {
+    type Error = ExtractPathError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.params.next() {
+            Some(param) => seed.deserialize(ValueDeserializer { param }).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.params.len())
+    }
+}
+
+struct MapDeserializer<'a, 'de> {
+    params: std::vec::IntoIter<&'a Param<'de>>,
+    value: Option<&'a Param<'de>>,
+}
+
+impl<'a, 'de> MapAccess<'de> for MapDeserializer<'a, 'de> {
+    type Error = ExtractPathError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.params.next() {
+            Some(param) => {
+                self.value = Some(param);
+                seed.deserialize(BorrowedStrDeserializer::new(param.name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(param) => seed.deserialize(ValueDeserializer { param }),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.params.len())
+    }
+}
+
+/// 反序列化单个路径参数的值。
+struct ValueDeserializer<'a, 'de> {
+    param: &'a Param<'de>,
+}
+
+impl<'a, 'de> ValueDeserializer<'a, 'de> {
+    fn parse<T>(&self) -> Result<T, ExtractPathError>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.param
+            .value
+            .parse()
+            .map_err(|e: T::Err| ExtractPathError::InvalidParam {
+                name: self.param.name.to_owned(),
+                source: e.into(),
+            })
+    }
+
+    fn unsupported(&self, kind: &str) -> ExtractPathError {
+        ExtractPathError::InvalidParam {
+            name: self.param.name.to_owned(),
+            source: format!("unsupported type `{kind}`").into(),
+        }
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for ValueDeserializer<'a, 'de> {
+    type Error = ExtractPathError;
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match &self.param.value {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_str(value),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match &self.param.value {
+            Cow::Borrowed(value) => visitor.visit_borrowed_bytes(value.as_bytes()),
+            Cow::Owned(value) => visitor.visit_bytes(value.as_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("sequence"))
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("tuple"))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("tuple struct"))
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("map"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("struct"))
+    }
+
+    forward_to_deserialize_any! {
+        str string unit unit_struct identifier ignored_any
+    }
+}
+
+impl<'a, 'de> EnumAccess<'de> for ValueDeserializer<'a, 'de> {
+    type Error = ExtractPathError;
+    type Variant = UnitVariant;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self).map(|v| (v, UnitVariant))
+    }
+}
+
+struct UnitVariant;
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = ExtractPathError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(de::Error::custom("newtype variants are not supported"))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("tuple variants are not supported"))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("struct variants are not supported"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::{decode, PathDeserializer};
+    use crate::extract::ExtractPathError;
+
+    fn params(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn from_params<T>(params: &[(String, String)]) -> Result<T, ExtractPathError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        T::deserialize(PathDeserializer::new(&decode(params)?))
+    }
+
+    #[test]
+    fn single() {
+        let params = params(&[("id", "42")]);
+        assert_eq!(from_params::<u32>(&params).unwrap(), 42);
+        assert_eq!(from_params::<String>(&params).unwrap(), "42");
+    }
+
+    #[test]
+    fn tuple() {
+        let params = params(&[("id", "42"), ("slug", "hello%20world")]);
+        assert_eq!(
+            from_params::<(u32, String)>(&params).unwrap(),
+            (42, "hello world".to_owned())
+        );
+        assert!(matches!(
+            from_params::<(u32, String, String)>(&params),
+            Err(ExtractPathError::WrongNumberOfParams {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn structure() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Params {
+            id: u64,
+            slug: String,
+        }
+
+        let params = params(&[("slug", "a-b"), ("id", "7")]);
+        assert_eq!(
+            from_params::<Params>(&params).unwrap(),
+            Params {
+                id: 7,
+                slug: "a-b".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn map() {
+        let params = params(&[("a", "1"), ("b", "2"), ("a", "3")]);
+        let map = from_params::<HashMap<String, u8>>(&params).unwrap();
+        assert_eq!(
+            map,
+            HashMap::from([("a".to_owned(), 3), ("b".to_owned(), 2)])
+        );
+    }
+
+    #[test]
+    fn repeated() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Params {
+            a: u8,
+            b: u8,
+        }
+
+        // 序列、元组和结构体与映射一样只保留最后一个同名参数。
+        let repeated = params(&[("a", "1"), ("b", "2"), ("a", "3")]);
+        assert_eq!(from_params::<Vec<u8>>(&repeated).unwrap(), [2, 3]);
+        assert_eq!(from_params::<(u8, u8)>(&repeated).unwrap(), (2, 3));
+        assert_eq!(
+            from_params::<Params>(&repeated).unwrap(),
+            Params { a: 3, b: 2 }
+        );
+
+        let single = params(&[("id", "1"), ("id", "2")]);
+        assert_eq!(from_params::<u8>(&single).unwrap(), 2);
+        assert_eq!(from_params::<(u8,)>(&single).unwrap(), (2,));
+    }
+
+    #[test]
+    fn invalid_param() {
+        let params = params(&[("slug", "a"), ("id", "x")]);
+        match from_params::<(String, u32)>(&params) {
+            Err(ExtractPathError::InvalidParam { name, .. }) => assert_eq!(name, "id"),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_param() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Params {
+            id: u64,
+            slug: String,
+        }
+
+        let params = params(&[("id", "7")]);
+        match from_params::<Params>(&params) {
+            Err(ExtractPathError::MissingParam { name }) => assert_eq!(name, "slug"),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn invalid_encoding() {
+        let params = params(&[("id", "%FF")]);
+        match from_params::<String>(&params) {
+            Err(ExtractPathError::InvalidEncoding { name, .. }) => assert_eq!(name, "id"),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+}
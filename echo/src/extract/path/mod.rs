@@ -0,0 +1,207 @@
+mod deserializer;
+
+use std::fmt;
+use std::future::{ready, Ready};
+use std::str::FromStr;
+use std::str::Utf8Error;
+
+use echo_core::http::request::Parts;
+use echo_core::http::Extensions;
+use echo_core::{BoxError, Request};
+use serde::de::{self, DeserializeOwned};
+use serde::Deserialize;
+
+use crate::route::PathParams;
+
+use super::FromRequestParts;
+
+/// 提取名为 `name` 的路径参数。
+///
+/// 与 [`path_params`] 和 [`Path`] 一样，参数的值会先进行百分号解码。
+pub fn path<T>(req: &Request, name: &str) -> Result<T, ExtractPathError>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    let param = find(req.extensions(), name)
+        .ok_or_else(|| ExtractPathError::MissingParam { name: name.into() })?;
+    let param = percent_encoding::percent_decode_str(param)
+        .decode_utf8()
+        .map_err(|e| ExtractPathError::InvalidEncoding {
+            name: name.into(),
+            source: e,
+        })?;
+    parse(name, &param)
+}
+
+/// 将所有路径参数反序列化为 `T`。
+///
+/// `T` 可以是结构体或映射（按名称匹配参数）、元组或序列（按顺序匹配参数），
+/// 也可以是只匹配单个参数的基本类型。参数的值会先进行百分号解码。
+pub fn path_params<'de, T>(req: &'de Request) -> Result<T, ExtractPathError>
+where
+    T: Deserialize<'de>,
+{
+    from_extensions(req.extensions())
+}
+
+fn from_extensions<'de, T>(extensions: &'de Extensions) -> Result<T, ExtractPathError>
+where
+    T: Deserialize<'de>,
+{
+    let params = extensions
+        .get::<PathParams>()
+        .map(|params| params.get_ref().as_slice())
+        .unwrap_or_default();
+    let params = deserializer::decode(params)?;
+    T::deserialize(deserializer::PathDeserializer::new(&params))
+}
+
+fn find<'a>(extensions: &'a Extensions, name: &str) -> Option<&'a str> {
+    extensions
+        .get::<PathParams>()
+        .map(|params| params.get_ref())
+        .and_then(|params| params.iter().rev().find(|(k, _)| k == name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn parse<T>(name: &str, param: &str) -> Result<T, ExtractPathError>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    param
+        .parse::<T>()
+        .map_err(|e| ExtractPathError::InvalidParam {
+            name: name.into(),
+            source: e.into(),
+        })
+}
+
+/// 通过 [`path_params`] 提取路径参数。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Path<T>(pub T);
+
+impl<T> FromRequestParts for Path<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ExtractPathError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(from_extensions(&parts.extensions).map(Path))
+    }
+}
+
+#[derive(Debug)]
+pub enum ExtractPathError {
+    MissingParam { name: String },
+    InvalidParam { name: String, source: BoxError },
+    InvalidEncoding { name: String, source: Utf8Error },
+    WrongNumberOfParams { expected: usize, actual: usize },
+    FailedToDeserialize { message: String },
+}
+
+impl fmt::Display for ExtractPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractPathError::MissingParam { name } => {
+                write!(f, "missing path param `{name}`")
+            }
+            ExtractPathError::InvalidParam { name, source } => {
+                write!(f, "invalid path param `{name}` ({source})")
+            }
+            ExtractPathError::InvalidEncoding { name, source } => {
+                write!(f, "failed to percent-decode path param `{name}` ({source})")
+            }
+            ExtractPathError::WrongNumberOfParams { expected, actual } => {
+                write!(
+                    f,
+                    "wrong number of path params, expected {expected} but got {actual}"
+                )
+            }
+            ExtractPathError::FailedToDeserialize { message } => {
+                write!(f, "failed to deserialize path params ({message})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractPathError {}
+
+impl de::Error for ExtractPathError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        ExtractPathError::FailedToDeserialize {
+            message: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        ExtractPathError::MissingParam { name: field.into() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::service::Service;
+    use echo_core::{Request, Response};
+    use serde::Deserialize;
+
+    use super::{path, ExtractPathError, Path};
+    use crate::handler::handler;
+    use crate::route::Router;
+
+    fn request(uri: &str) -> Request {
+        Request::builder()
+            .uri(uri)
+            .body(Default::default())
+            .unwrap()
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn decode() {
+        async fn f(Path(name): Path<String>, req: Request) -> String {
+            let raw: String = path(&req, "name").unwrap();
+            format!("{name}|{raw}")
+        }
+
+        let router = Router::new().route("/users/:name", handler(f));
+        let res = router.call(request("/users/a%20b")).await.unwrap();
+        assert_eq!(body(res).await, "a b|a b");
+        let res = router.call(request("/users/%2525")).await.unwrap();
+        assert_eq!(body(res).await, "%25|%25");
+
+        let e = router.call(request("/users/%FF")).await.unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<ExtractPathError>(),
+            Some(ExtractPathError::InvalidEncoding { name, .. }) if name == "name"
+        ));
+    }
+
+    #[tokio::test]
+    async fn repeated() {
+        #[derive(Deserialize)]
+        struct Params {
+            id: u32,
+        }
+
+        async fn f(Path((id,)): Path<(u32,)>, Path(params): Path<Params>, req: Request) -> String {
+            let single: u32 = path(&req, "id").unwrap();
+            format!("{id} {} {single}", params.id)
+        }
+
+        // 内层路由的同名参数覆盖外层路由的参数。
+        let router = Router::new().scope("/a/:id", Router::new().route("/b/:id", handler(f)));
+        let res = router.call(request("/a/1/b/2")).await.unwrap();
+        assert_eq!(body(res).await, "2 2 2");
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::future::{ready, Ready};
+
+use echo_core::http::request::Parts;
+use echo_core::http::Uri;
+use echo_core::Request;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::FromRequestParts;
+
+mod nested;
+
+pub use nested::{nested_query, NestedQuery, NestedQueryConfig, NestedQueryError};
+
+pub fn query<'de, T>(req: &'de Request) -> Result<T, ExtractQueryError>
+where
+    T: Deserialize<'de>,
+{
+    from_uri(req.uri())
+}
+
+fn from_uri<'de, T>(uri: &'de Uri) -> Result<T, ExtractQueryError>
+where
+    T: Deserialize<'de>,
+{
+    let query = uri.query().unwrap_or_default();
+    serde_urlencoded::from_str(query).map_err(ExtractQueryError::FailedToDeserialize)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+impl<T> FromRequestParts for Query<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ExtractQueryError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(from_uri(&parts.uri).map(Query))
+    }
+}
+
+#[derive(Debug)]
+pub enum ExtractQueryError {
+    FailedToDeserialize(serde_urlencoded::de::Error),
+    FailedToDeserializeNested(NestedQueryError),
+}
+
+impl fmt::Display for ExtractQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractQueryError::FailedToDeserialize(e) => {
+                write!(f, "failed to deserialize query string ({e})")
+            }
+            ExtractQueryError::FailedToDeserializeNested(e) => {
+                write!(f, "failed to deserialize query string ({e})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractQueryError {}
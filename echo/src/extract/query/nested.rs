@@ -0,0 +1,812 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{ready, Ready};
+use std::str::FromStr;
+
+use echo_core::http::request::Parts;
+use echo_core::http::Extensions;
+use echo_core::{BoxError, Request};
+use serde::de::value::{BorrowedStrDeserializer, StrDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess};
+use serde::de::{VariantAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+use super::ExtractQueryError;
+use crate::extract::FromRequestParts;
+
+/// 嵌套查询字符串的解析配置。
+///
+/// 可以通过请求扩展（例如 [`add_extension`](crate::middleware::add_extension) 中间件）
+/// 为 [`nested_query`]、[`NestedQuery`] 以及嵌套表单提取器设置配置，未设置时使用默认配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NestedQueryConfig {
+    max_depth: usize,
+    max_array_len: usize,
+    max_params: usize,
+}
+
+impl NestedQueryConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置键的最大嵌套深度，`a[b][c]` 的深度为 2，默认为 5。
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// 设置单个数组的最大长度，同时限制数组下标的最大值，默认为 100。
+    pub fn max_array_len(mut self, max: usize) -> Self {
+        self.max_array_len = max;
+        self
+    }
+
+    /// 设置参数的最大数量，默认为 1000。
+    pub fn max_params(mut self, max: usize) -> Self {
+        self.max_params = max;
+        self
+    }
+
+    /// 解析查询字符串并反序列化为 `T`。
+    pub fn deserialize<'de, T>(&self, input: &'de str) -> Result<T, NestedQueryError>
+    where
+        T: Deserialize<'de>,
+    {
+        self.deserialize_bytes(input.as_bytes())
+    }
+
+    pub(crate) fn deserialize_bytes<'de, T>(&self, input: &'de [u8]) -> Result<T, NestedQueryError>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(ValueDeserializer {
+            key: "",
+            value: &self.parse(input)?,
+        })
+    }
+
+    pub(crate) fn from_extensions(extensions: &Extensions) -> Self {
+        extensions.get::<Self>().copied().unwrap_or_default()
+    }
+
+    fn parse<'de>(&self, input: &'de [u8]) -> Result<Value<'de>, NestedQueryError> {
+        let mut root = Map::default();
+        for (i, (key, value)) in form_urlencoded::parse(input).enumerate() {
+            if i >= self.max_params {
+                return Err(NestedQueryError::ParamLimitExceeded {
+                    limit: self.max_params,
+                });
+            }
+            let segments = self.split_key(&key)?;
+            self.insert(&mut root, &key, &segments, value)?;
+        }
+        Ok(Value::Map(root))
+    }
+
+    /// 将 `a[b][]` 形式的键拆分为 `["a", "b", ""]`。
+    fn split_key<'k>(&self, key: &'k str) -> Result<Vec<&'k str>, NestedQueryError> {
+        let invalid = || NestedQueryError::InvalidKey {
+            key: key.to_owned(),
+        };
+
+        let (first, mut rest) = match key.find('[') {
+            Some(i) => key.split_at(i),
+            None => return Ok(vec![key]),
+        };
+
+        let mut segments = vec![first];
+        while !rest.is_empty() {
+            let end = match (rest.strip_prefix('['), rest.find(']')) {
+                (Some(_), Some(end)) => end,
+                _ => return Err(invalid()),
+            };
+            let segment = &rest[1..end];
+            if segment.contains('[') {
+                return Err(invalid());
+            }
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+
+        if segments.len() - 1 > self.max_depth {
+            return Err(NestedQueryError::DepthLimitExceeded {
+                key: key.to_owned(),
+                limit: self.max_depth,
+            });
+        }
+        // 只允许 `[]` 出现在键的末尾。
+        if segments[..segments.len() - 1].iter().any(|s| s.is_empty()) {
+            return Err(invalid());
+        }
+        if segments[1..]
+            .iter()
+            .any(|s| s.parse::<usize>().is_ok_and(|i| i >= self.max_array_len))
+        {
+            return Err(NestedQueryError::ArrayLimitExceeded {
+                key: key.to_owned(),
+                limit: self.max_array_len,
+            });
+        }
+
+        Ok(segments)
+    }
+
+    fn insert<'de>(
+        &self,
+        map: &mut Map<'de>,
+        key: &str,
+        segments: &[&str],
+        value: Cow<'de, str>,
+    ) -> Result<(), NestedQueryError> {
+        let (segment, rest) = match segments {
+            [segment, rest @ ..] => (*segment, rest),
+            [] => {
+                return Err(NestedQueryError::InvalidKey {
+                    key: key.to_owned(),
+                })
+            }
+        };
+
+        let index = map.index.get(segment).copied();
+
+        // `a[]=1` 与 `a=1` 等价，重复出现的键会组成数组。
+        if rest.is_empty() || rest == [""] {
+            let entry = match index {
+                Some(i) => &mut map.entries[i].1,
+                None => {
+                    map.push(segment, Value::String(value));
+                    return Ok(());
+                }
+            };
+            match entry {
+                Value::String(_) => {
+                    let first = std::mem::replace(entry, Value::Seq(Vec::new()));
+                    if let Value::Seq(seq) = entry {
+                        seq.push(first);
+                        seq.push(Value::String(value));
+                    }
+                }
+                Value::Seq(seq) => seq.push(Value::String(value)),
+                Value::Map(_) => {
+                    return Err(NestedQueryError::InvalidKey {
+                        key: key.to_owned(),
+                    })
+                }
+            }
+            if let Value::Seq(seq) = entry {
+                if seq.len() > self.max_array_len {
+                    return Err(NestedQueryError::ArrayLimitExceeded {
+                        key: key.to_owned(),
+                        limit: self.max_array_len,
+                    });
+                }
+            }
+            return Ok(());
+        }
+
+        let i = match index {
+            Some(i) => i,
+            None => map.push(segment, Value::Map(Map::default())),
+        };
+        match &mut map.entries[i].1 {
+            Value::Map(child) => self.insert(child, key, rest, value),
+            _ => Err(NestedQueryError::InvalidKey {
+                key: key.to_owned(),
+            }),
+        }
+    }
+}
+
+impl Default for NestedQueryConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            max_array_len: 100,
+            max_params: 1000,
+        }
+    }
+}
+
+/// 使用请求扩展中的 [`NestedQueryConfig`] 解析查询字符串。
+///
+/// 与 [`query`](crate::extract::query) 不同，支持 `?tag=a&tag=b` 形式的重复键以及
+/// `?filter[status]=open` 形式的嵌套结构。
+pub fn nested_query<'de, T>(req: &'de Request) -> Result<T, ExtractQueryError>
+where
+    T: Deserialize<'de>,
+{
+    NestedQueryConfig::from_extensions(req.extensions())
+        .deserialize(req.uri().query().unwrap_or_default())
+        .map_err(ExtractQueryError::FailedToDeserializeNested)
+}
+
+/// 通过 [`nested_query`] 提取查询字符串。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NestedQuery<T>(pub T);
+
+impl<T> FromRequestParts for NestedQuery<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ExtractQueryError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        let res = NestedQueryConfig::from_extensions(&parts.extensions)
+            .deserialize(parts.uri.query().unwrap_or_default())
+            .map(NestedQuery)
+            .map_err(ExtractQueryError::FailedToDeserializeNested);
+        ready(res)
+    }
+}
+
+#[derive(Debug)]
+pub enum NestedQueryError {
+    InvalidKey { key: String },
+    InvalidValue { key: String, source: BoxError },
+    DepthLimitExceeded { key: String, limit: usize },
+    ArrayLimitExceeded { key: String, limit: usize },
+    ParamLimitExceeded { limit: usize },
+    Custom { message: String },
+}
+
+impl fmt::Display for NestedQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NestedQueryError::InvalidKey { key } => write!(f, "invalid key `{key}`"),
+            NestedQueryError::InvalidValue { key, source } => {
+                write!(f, "invalid value for key `{key}` ({source})")
+            }
+            NestedQueryError::DepthLimitExceeded { key, limit } => {
+                write!(f, "key `{key}` exceeds the maximum depth of {limit}")
+            }
+            NestedQueryError::ArrayLimitExceeded { key, limit } => {
+                write!(f, "key `{key}` exceeds the maximum array length of {limit}")
+            }
+            NestedQueryError::ParamLimitExceeded { limit } => {
+                write!(f, "number of params exceeds the maximum of {limit}")
+            }
+            NestedQueryError::Custom { message } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for NestedQueryError {}
+
+impl de::Error for NestedQueryError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        NestedQueryError::Custom {
+            message: msg.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Value<'de> {
+    String(Cow<'de, str>),
+    Seq(Vec<Value<'de>>),
+    Map(Map<'de>),
+}
+
+/// 保持插入顺序的映射，通过索引查找键。
+#[derive(Debug, Default)]
+struct Map<'de> {
+    entries: Vec<(Cow<'de, str>, Value<'de>)>,
+    index: HashMap<Cow<'de, str>, usize>,
+}
+
+impl<'de> Map<'de> {
+    fn push(&mut self, key: &str, value: Value<'de>) -> usize {
+        let i = self.entries.len();
+        self.entries.push((Cow::Owned(key.to_owned()), value));
+        self.index.insert(Cow::Owned(key.to_owned()), i);
+        i
+    }
+}
+
+struct ValueDeserializer<'a, 'de> {
+    key: &'a str,
+    value: &'a Value<'de>,
+}
+
+impl<'a, 'de> ValueDeserializer<'a, 'de> {
+    fn string(&self) -> Result<&'a Cow<'de, str>, NestedQueryError> {
+        match self.value {
+            Value::String(s) => Ok(s),
+            // 期望单个值时，重复的键以最后一个值为准。
+            Value::Seq(seq) => match seq.last() {
+                Some(Value::String(s)) => Ok(s),
+                _ => Err(self.unexpected("a sequence")),
+            },
+            Value::Map(_) => Err(self.unexpected("a map")),
+        }
+    }
+
+    fn parse<T>(&self) -> Result<T, NestedQueryError>
+    where
+        T: FromStr,
+        T::Err: Into<BoxError>,
+    {
+        self.string()?
+            .parse()
+            .map_err(|e: T::Err| NestedQueryError::InvalidValue {
+                key: self.key.to_owned(),
+                source: e.into(),
+            })
+    }
+
+    fn unexpected(&self, found: &str) -> NestedQueryError {
+        NestedQueryError::InvalidValue {
+            key: self.key.to_owned(),
+            source: format!("unexpected {found}").into(),
+        }
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for ValueDeserializer<'a, 'de> {
+    type Error = NestedQueryError;
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String(_) => self.deserialize_str(visitor),
+            Value::Seq(_) => self.deserialize_seq(visitor),
+            Value::Map(_) => self.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.string()? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_str(s),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.string()? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            Cow::Owned(s) => visitor.visit_bytes(s.as_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let key = self.key;
+        match self.value {
+            Value::String(_) => visitor.visit_seq(SeqDeserializer {
+                key,
+                values: std::slice::from_ref(self.value)
+                    .iter()
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            }),
+            Value::Seq(seq) => visitor.visit_seq(SeqDeserializer {
+                key,
+                values: seq.iter().collect::<Vec<_>>().into_iter(),
+            }),
+            // `a[1]=x&a[0]=y` 按下标排序后作为数组。
+            Value::Map(map) => {
+                let mut indexed = map
+                    .entries
+                    .iter()
+                    .map(|(k, v)| k.parse::<usize>().map(|i| (i, v)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| self.unexpected("a map"))?;
+                indexed.sort_by_key(|(i, _)| *i);
+                visitor.visit_seq(SeqDeserializer {
+                    key,
+                    values: indexed
+                        .into_iter()
+                        .map(|(_, v)| v)
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                })
+            }
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Map(map) => visitor.visit_map(MapDeserializer {
+                parent: self.key,
+                key: String::new(),
+                entries: map.entries.iter(),
+                value: None,
+            }),
+            Value::String(_) => Err(self.unexpected("a single value")),
+            Value::Seq(_) => Err(self.unexpected("a sequence")),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(EnumDeserializer {
+            variant: self.string()?,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        identifier ignored_any
+    }
+}
+
+struct SeqDeserializer<'a, 'de> {
+    key: &'a str,
+    values: std::vec::IntoIter<&'a Value<'de>>,
+}
+
+impl<'a, 'de> SeqAccess<'de> for SeqDeserializer<'a, 'de> {
+    type Error = NestedQueryError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.values.next() {
+            Some(value) => seed
+                .deserialize(ValueDeserializer {
+                    key: self.key,
+                    value,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+struct MapDeserializer<'a, 'de> {
+    parent: &'a str,
+    key: String,
+    entries: std::slice::Iter<'a, (Cow<'de, str>, Value<'de>)>,
+    value: Option<&'a Value<'de>>,
+}
+
+impl<'a, 'de> MapAccess<'de> for MapDeserializer<'a, 'de> {
+    type Error = NestedQueryError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                self.key = if self.parent.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}[{key}]", self.parent)
+                };
+                match key {
+                    Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+                    Cow::Owned(key) => seed.deserialize(StrDeserializer::new(key)),
+                }
+                .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer {
+                key: &self.key,
+                value,
+            }),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct EnumDeserializer<'a, 'de> {
+    variant: &'a Cow<'de, str>,
+}
+
+impl<'a, 'de> EnumAccess<'de> for EnumDeserializer<'a, 'de> {
+    type Error = NestedQueryError;
+    type Variant = UnitVariant;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.variant {
+            Cow::Borrowed(variant) => seed.deserialize(BorrowedStrDeserializer::new(variant)),
+            Cow::Owned(variant) => seed.deserialize(StrDeserializer::new(variant)),
+        }
+        .map(|v| (v, UnitVariant))
+    }
+}
+
+struct UnitVariant;
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = NestedQueryError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(de::Error::custom("newtype variants are not supported"))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("tuple variants are not supported"))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("struct variants are not supported"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::{NestedQueryConfig, NestedQueryError};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Filter {
+        status: String,
+        page: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Search {
+        tag: Vec<String>,
+        filter: Filter,
+    }
+
+    fn parse<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T, NestedQueryError> {
+        NestedQueryConfig::new().deserialize(input)
+    }
+
+    #[test]
+    fn nested() {
+        let search: Search = parse("tag=a&tag=b&filter[status]=open&filter[page]=2").unwrap();
+        assert_eq!(
+            search,
+            Search {
+                tag: vec!["a".into(), "b".into()],
+                filter: Filter {
+                    status: "open".into(),
+                    page: Some(2),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        let map: HashMap<String, Vec<u32>> = parse("a[]=1&a[]=2&b=3").unwrap();
+        assert_eq!(map["a"], [1, 2]);
+        assert_eq!(map["b"], [3]);
+
+        let map: HashMap<String, Vec<u32>> = parse("a[1]=2&a[0]=1").unwrap();
+        assert_eq!(map["a"], [1, 2]);
+
+        let map: HashMap<String, u32> = parse("a=1&a=2").unwrap();
+        assert_eq!(map["a"], 2);
+    }
+
+    #[test]
+    fn limits() {
+        let config = NestedQueryConfig::new().max_depth(1).max_array_len(2);
+
+        let res = config.deserialize::<HashMap<String, String>>("a[b][c]=1");
+        assert!(matches!(
+            res,
+            Err(NestedQueryError::DepthLimitExceeded { limit: 1, .. })
+        ));
+
+        let res = config.deserialize::<HashMap<String, Vec<u32>>>("a=1&a=2&a=3");
+        assert!(matches!(
+            res,
+            Err(NestedQueryError::ArrayLimitExceeded { limit: 2, .. })
+        ));
+
+        let res = config.deserialize::<HashMap<String, Vec<u32>>>("a[5]=1");
+        assert!(matches!(
+            res,
+            Err(NestedQueryError::ArrayLimitExceeded { limit: 2, .. })
+        ));
+
+        let input = (0..1001).map(|i| format!("k{i}=1")).collect::<Vec<_>>();
+        let res = parse::<HashMap<String, u32>>(&input.join("&"));
+        assert!(matches!(
+            res,
+            Err(NestedQueryError::ParamLimitExceeded { limit: 1000 })
+        ));
+        let res = parse::<HashMap<String, u32>>(&input[..1000].join("&"));
+        assert_eq!(res.unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn many_keys() {
+        // 查找键不应随键的数量线性增长，否则解析大量不同的键需要平方时间。
+        let input = (0..50_000)
+            .map(|i| format!("k{i}[v]={i}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let config = NestedQueryConfig::new().max_params(usize::MAX);
+        let map: HashMap<String, HashMap<String, u32>> = config.deserialize(&input).unwrap();
+        assert_eq!(map.len(), 50_000);
+        assert_eq!(map["k49999"]["v"], 49_999);
+    }
+
+    #[test]
+    fn invalid() {
+        let res = parse::<HashMap<String, String>>("a[b=1");
+        assert!(matches!(res, Err(NestedQueryError::InvalidKey { .. })));
+
+        let res = parse::<HashMap<String, String>>("a=1&a[b]=2");
+        assert!(matches!(res, Err(NestedQueryError::InvalidKey { .. })));
+
+        let res = parse::<Filter>("status=open&page=x");
+        assert!(
+            matches!(res, Err(NestedQueryError::InvalidValue { ref key, .. }) if key == "page")
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::future::{ready, Ready};
+use std::net::SocketAddr;
+
+use echo_core::http::request::Parts;
+use echo_core::Request;
+
+use super::{ExtractExtensionError, FromRequestParts};
+
+/// 获取对端地址，由 [`Server`](crate::server::Server) 保存在请求扩展中。
+pub fn remote_addr(req: &Request) -> Option<SocketAddr> {
+    req.extensions().get::<RemoteAddr>().map(|addr| addr.0)
+}
+
+/// 连接的对端地址。
+///
+/// 服务位于反向代理之后时，这是代理的地址而不是客户端的地址。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RemoteAddr(pub SocketAddr);
+
+impl FromRequestParts for RemoteAddr {
+    type Error = ExtractExtensionError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(parts.extensions.get::<RemoteAddr>().copied().ok_or(
+            ExtractExtensionError::MissingExtension {
+                type_name: std::any::type_name::<RemoteAddr>(),
+            },
+        ))
+    }
+}
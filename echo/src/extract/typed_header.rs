@@ -0,0 +1,168 @@
+use std::fmt;
+use std::future::{ready, Ready};
+use std::ops::Deref;
+
+use echo_core::http::header::HeaderName;
+use echo_core::http::request::Parts;
+use echo_core::http::HeaderMap;
+use echo_core::response::{IntoResponse, IntoResponseParts};
+use echo_core::{Request, Response};
+use headers::{Header, HeaderMapExt};
+
+use super::FromRequestParts;
+
+/// 按类型解析请求头，会读取同名请求头的所有值，因此可以正确处理多值及逗号分隔的请求头。
+///
+/// 常用的类型见 [`headers`](crate::headers)，也可以通过实现 [`Header`] 自定义。
+pub fn typed_header<T>(req: &Request) -> Result<T, ExtractTypedHeaderError>
+where
+    T: Header,
+{
+    from_headers(req.headers())
+}
+
+fn from_headers<T>(headers: &HeaderMap) -> Result<T, ExtractTypedHeaderError>
+where
+    T: Header,
+{
+    let mut values = headers.get_all(T::name()).iter().peekable();
+    if values.peek().is_none() {
+        return Err(ExtractTypedHeaderError::MissingHeader { name: T::name() });
+    }
+    T::decode(&mut values).map_err(|source| ExtractTypedHeaderError::InvalidHeader {
+        name: T::name(),
+        source,
+    })
+}
+
+/// 通过 [`typed_header`] 提取请求头，也可以作为响应的一部分插入响应头。
+///
+/// # 例子
+///
+/// ```
+/// use echo::extract::TypedHeader;
+/// use echo::headers::authorization::Bearer;
+/// use echo::headers::{Authorization, CacheControl, ContentType};
+///
+/// async fn handler(
+///     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+/// ) -> (TypedHeader<CacheControl>, String) {
+///     let cache_control = CacheControl::new().with_no_store();
+///     (TypedHeader(cache_control), auth.token().to_owned())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypedHeader<T>(pub T);
+
+impl<T> Deref for TypedHeader<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> FromRequestParts for TypedHeader<T>
+where
+    T: Header,
+{
+    type Error = ExtractTypedHeaderError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(from_headers(&parts.headers).map(TypedHeader))
+    }
+}
+
+impl<T> IntoResponseParts for TypedHeader<T>
+where
+    T: Header,
+{
+    fn into_response_parts(self, res: &mut Response) {
+        res.headers_mut().typed_insert(self.0);
+    }
+}
+
+impl<T> IntoResponse for TypedHeader<T>
+where
+    T: Header,
+{
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
+
+#[derive(Debug)]
+pub enum ExtractTypedHeaderError {
+    MissingHeader {
+        name: &'static HeaderName,
+    },
+    InvalidHeader {
+        name: &'static HeaderName,
+        source: headers::Error,
+    },
+}
+
+impl fmt::Display for ExtractTypedHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractTypedHeaderError::MissingHeader { name } => {
+                write!(f, "missing request header `{name}`")
+            }
+            ExtractTypedHeaderError::InvalidHeader { name, source } => {
+                write!(f, "invalid request header `{name}` ({source})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractTypedHeaderError {}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::http::header::{self, HeaderValue};
+    use echo_core::http::HeaderMap;
+    use echo_core::response::IntoResponse;
+    use headers::{ContentLength, ETag, IfNoneMatch};
+
+    use super::{from_headers, ExtractTypedHeaderError, TypedHeader};
+
+    #[test]
+    fn multi_valued() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"a\", \"b\""),
+        );
+        headers.append(header::IF_NONE_MATCH, HeaderValue::from_static("\"c\""));
+
+        let if_none_match = from_headers::<IfNoneMatch>(&headers).unwrap();
+        for etag in ["\"a\"", "\"b\"", "\"c\""] {
+            assert!(!if_none_match.precondition_passes(&etag.parse::<ETag>().unwrap()));
+        }
+        assert!(if_none_match.precondition_passes(&"\"d\"".parse::<ETag>().unwrap()));
+    }
+
+    #[test]
+    fn missing_and_invalid() {
+        let mut headers = HeaderMap::new();
+        let res = from_headers::<ContentLength>(&headers);
+        assert!(matches!(
+            res,
+            Err(ExtractTypedHeaderError::MissingHeader { .. })
+        ));
+
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("abc"));
+        let res = from_headers::<ContentLength>(&headers);
+        assert!(matches!(
+            res,
+            Err(ExtractTypedHeaderError::InvalidHeader { .. })
+        ));
+    }
+
+    #[test]
+    fn insert() {
+        let res = (TypedHeader(ContentLength(3)), "abc").into_response();
+        assert_eq!(res.headers()[header::CONTENT_LENGTH], "3");
+    }
+}
@@ -0,0 +1,375 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use echo_core::body::BodyExt;
+use echo_core::http::request::Parts;
+use echo_core::http::{header, Method};
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+
+use super::file::{self, Precompressed, Representation, SegmentBody};
+use super::ServeDir;
+use crate::response::Redirect;
+
+/// 编译时嵌入的文件，由 [`embed_dir!`](super::embed_dir) 生成。
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedFile {
+    /// 相对于嵌入目录的路径，以 `/` 分隔。
+    pub path: &'static str,
+    pub contents: &'static [u8],
+    /// 根据内容计算的强 ETag，包含引号。
+    pub etag: &'static str,
+    pub mime: &'static str,
+    /// 预先压缩的内容，只有比原始内容小时才会保留。
+    pub gzip: Option<&'static [u8]>,
+    pub br: Option<&'static [u8]>,
+}
+
+/// 返回嵌入文件的服务，通常由 [`embed_dir!`](super::embed_dir) 创建。
+///
+/// 与 [`ServeDir`] 的行为相同，但是没有 `Last-Modified`，并且预压缩内容总是会被使用。
+/// 调试构建时 `embed_dir!` 生成的服务直接从磁盘读取文件，修改后无需重新编译。
+#[derive(Debug, Clone)]
+pub struct EmbeddedDir {
+    source: Source,
+    index_file: Cow<'static, str>,
+    fallback: Option<Cow<'static, str>>,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Embedded(&'static [EmbeddedFile]),
+    Disk(PathBuf),
+}
+
+impl EmbeddedDir {
+    /// `files` 必须按照 `path` 排序。
+    pub const fn new(files: &'static [EmbeddedFile]) -> Self {
+        Self {
+            source: Source::Embedded(files),
+            index_file: Cow::Borrowed("index.html"),
+            fallback: None,
+        }
+    }
+
+    /// 从磁盘上的 `root` 目录读取文件。
+    pub fn from_disk(root: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::Disk(root.into()),
+            index_file: Cow::Borrowed("index.html"),
+            fallback: None,
+        }
+    }
+
+    /// 参见 [`ServeDir::index_file`]。
+    pub fn index_file(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.index_file = name.into();
+        self
+    }
+
+    /// 设置文件不存在时返回的文件，状态码为 `200 OK`，适用于单页应用。
+    ///
+    /// 与 [`ServeDir::fallback`] 不同，`path` 相对于嵌入的目录。
+    pub fn fallback(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.fallback = Some(path.into());
+        self
+    }
+
+    /// 文件是否被嵌入，从磁盘读取时返回 `false`。
+    pub fn is_embedded(&self) -> bool {
+        matches!(self.source, Source::Embedded(_))
+    }
+
+    /// 返回嵌入的文件，从磁盘读取时总是返回 `None`。
+    pub fn get(&self, path: &str) -> Option<&'static EmbeddedFile> {
+        match self.source {
+            Source::Embedded(files) => find(files, path),
+            Source::Disk(_) => None,
+        }
+    }
+
+    /// 按路径顺序遍历嵌入的文件，从磁盘读取时为空。
+    pub fn iter(&self) -> impl Iterator<Item = &'static EmbeddedFile> {
+        let files = match self.source {
+            Source::Embedded(files) => files,
+            Source::Disk(_) => &[],
+        };
+        files.iter()
+    }
+
+    fn serve_embedded(&self, files: &'static [EmbeddedFile], parts: &Parts) -> Response {
+        let found = file::normalize_path(parts.uri.path()).and_then(|path| {
+            if let Some(file) = find(files, &path) {
+                return Some(Ok(file));
+            }
+            if !is_dir(files, &path) {
+                return None;
+            }
+            if let Some(location) = file::directory_redirect(&parts.uri) {
+                return Some(Err(location));
+            }
+            if path.is_empty() {
+                find(files, &self.index_file)
+            } else {
+                find(files, &format!("{path}/{}", self.index_file))
+            }
+            .map(Ok)
+        });
+
+        let found = match found {
+            Some(Ok(file)) => Some(file),
+            Some(Err(location)) => return Redirect::temporary(&location).into_response(),
+            None => self
+                .fallback
+                .as_deref()
+                .and_then(|fallback| find(files, fallback)),
+        };
+
+        match found {
+            Some(file) => respond(file, parts),
+            None => file::not_found(),
+        }
+    }
+
+    fn disk_service(&self, root: &Path) -> ServeDir {
+        let dir = ServeDir::new(root).index_file(self.index_file.as_ref());
+        match &self.fallback {
+            Some(fallback) => dir.fallback(root.join(fallback.as_ref())),
+            None => dir,
+        }
+    }
+}
+
+impl<B> Service<Request<B>> for EmbeddedDir {
+    type Response = Response;
+    type Error = io::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, io::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request<B>) -> Self::Future<'_> {
+        let (parts, _) = req.into_parts();
+
+        Box::pin(async move {
+            if parts.method != Method::GET && parts.method != Method::HEAD {
+                return Ok(file::method_not_allowed());
+            }
+
+            match &self.source {
+                Source::Embedded(files) => Ok(self.serve_embedded(files, &parts)),
+                Source::Disk(root) => {
+                    let req = Request::from_parts(parts, ());
+                    self.disk_service(root).call(req).await
+                }
+            }
+        })
+    }
+}
+
+fn find(files: &'static [EmbeddedFile], path: &str) -> Option<&'static EmbeddedFile> {
+    files
+        .binary_search_by(|file| file.path.cmp(path))
+        .ok()
+        .map(|i| &files[i])
+}
+
+fn is_dir(files: &[EmbeddedFile], path: &str) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    let prefix = format!("{path}/");
+    let i = files.partition_point(|file| file.path < prefix.as_str());
+    files
+        .get(i)
+        .is_some_and(|file| file.path.starts_with(&prefix))
+}
+
+fn respond(file: &'static EmbeddedFile, parts: &Parts) -> Response {
+    let precompressed = Precompressed {
+        br: file.br.is_some(),
+        gzip: file.gzip.is_some(),
+    };
+
+    let mut selected = (file.contents, None);
+    if precompressed.is_enabled() && !parts.headers.contains_key(header::RANGE) {
+        if let Some((encoding, _)) = precompressed.candidates(&parts.headers).first() {
+            let contents = match *encoding {
+                "br" => file.br,
+                _ => file.gzip,
+            };
+            selected = (contents.unwrap(), Some(*encoding));
+        }
+    }
+    let (contents, encoding) = selected;
+
+    let etag = match encoding {
+        Some(encoding) => Cow::Owned(format!("{}-{encoding}\"", file.etag.trim_end_matches('"'))),
+        None => Cow::Borrowed(file.etag),
+    };
+
+    let representation = Representation {
+        len: contents.len() as u64,
+        etag: &etag,
+        modified: None,
+        mime: file.mime,
+        encoding,
+        vary: precompressed.is_enabled(),
+    };
+    let (mut res, segments) = file::respond(&parts.method, &parts.headers, &representation);
+    if !segments.is_empty() {
+        *res.body_mut() = SegmentBody::from_static(contents, segments).boxed();
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::http::{header, Method, Request, StatusCode};
+    use echo_core::service::Service;
+    use echo_core::Response;
+
+    use super::{EmbeddedDir, EmbeddedFile};
+
+    const fn file(path: &'static str, mime: &'static str, etag: &'static str) -> EmbeddedFile {
+        EmbeddedFile {
+            path,
+            contents: b"contents",
+            etag,
+            mime,
+            gzip: None,
+            br: None,
+        }
+    }
+
+    static FILES: [EmbeddedFile; 4] = [
+        EmbeddedFile {
+            gzip: Some(b"gzip"),
+            br: Some(b"br"),
+            ..file("app.js", "text/javascript", "\"app\"")
+        },
+        file("docs/index.html", "text/html", "\"docs\""),
+        file("index.html", "text/html", "\"index\""),
+        file("logo.png", "image/png", "\"logo\""),
+    ];
+
+    fn dir() -> EmbeddedDir {
+        EmbeddedDir::new(&FILES)
+    }
+
+    async fn call(
+        dir: &EmbeddedDir,
+        method: Method,
+        uri: &str,
+        headers: &[(&str, &str)],
+    ) -> Response {
+        let mut req = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        dir.call(req.body(()).unwrap()).await.unwrap()
+    }
+
+    async fn get(dir: &EmbeddedDir, uri: &str, headers: &[(&str, &str)]) -> Response {
+        call(dir, Method::GET, uri, headers).await
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn serve() {
+        let dir = dir();
+        assert!(dir.is_embedded());
+        assert_eq!(dir.get("logo.png").unwrap().mime, "image/png");
+
+        let res = get(&dir, "/logo.png", &[]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(res.headers()[header::ETAG], "\"logo\"");
+        assert!(!res.headers().contains_key(header::LAST_MODIFIED));
+        assert!(!res.headers().contains_key(header::VARY));
+        assert_eq!(body(res).await, "contents");
+
+        let res = get(&dir, "/missing.png", &[]).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let res = get(&dir, "/../logo.png", &[]).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = call(&dir, Method::HEAD, "/logo.png", &[]).await;
+        assert_eq!(res.headers()[header::CONTENT_LENGTH], "8");
+        assert!(body(res).await.is_empty());
+
+        let res = call(&dir, Method::POST, "/logo.png", &[]).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn etag() {
+        let dir = dir();
+        let res = get(&dir, "/logo.png", &[("if-none-match", "\"logo\"")]).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(body(res).await.is_empty());
+
+        let res = get(&dir, "/logo.png", &[("if-none-match", "\"other\"")]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = get(&dir, "/logo.png", &[("range", "bytes=0-2")]).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body(res).await, "con");
+    }
+
+    #[tokio::test]
+    async fn precompressed() {
+        let dir = dir();
+        let cases = [
+            ("gzip", Some("gzip"), "gzip", "\"app-gzip\""),
+            ("gzip, br", Some("br"), "br", "\"app-br\""),
+            ("identity", None, "contents", "\"app\""),
+        ];
+        for (accept, encoding, contents, etag) in cases {
+            let res = get(&dir, "/app.js", &[("accept-encoding", accept)]).await;
+            assert_eq!(
+                res.headers()
+                    .get(header::CONTENT_ENCODING)
+                    .map(|value| value.to_str().unwrap()),
+                encoding,
+                "{accept}"
+            );
+            assert_eq!(res.headers()[header::CONTENT_TYPE], "text/javascript");
+            assert_eq!(res.headers()[header::VARY], "accept-encoding");
+            assert_eq!(res.headers()[header::ETAG], etag);
+            assert_eq!(body(res).await, contents);
+        }
+
+        let res = get(
+            &dir,
+            "/app.js",
+            &[("accept-encoding", "br"), ("if-none-match", "\"app-br\"")],
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn index() {
+        let dir = dir();
+        let res = get(&dir, "/", &[]).await;
+        assert_eq!(res.headers()[header::ETAG], "\"index\"");
+        let res = get(&dir, "/docs/", &[]).await;
+        assert_eq!(res.headers()[header::ETAG], "\"docs\"");
+
+        let res = get(&dir, "/docs", &[]).await;
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(res.headers()[header::LOCATION], "./docs/");
+
+        let dir = dir.fallback("index.html");
+        let res = get(&dir, "/app/settings", &[]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::ETAG], "\"index\"");
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::VecDeque;
+use std::fs::Metadata;
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{ready, Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use echo_core::body::{Body, BodyExt, BoxBody, Bytes, Frame, SizeHint};
+use echo_core::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use echo_core::http::{Method, StatusCode, Uri};
+use echo_core::Response;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use percent_encoding::percent_decode_str;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::range::{parse_range, ParsedRange};
+use crate::extract::AcceptEncoding;
+
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// 需要查找的预压缩文件。
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Precompressed {
+    pub(crate) br: bool,
+    pub(crate) gzip: bool,
+}
+
+impl Precompressed {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.br || self.gzip
+    }
+
+    /// 按客户端的偏好返回应当尝试的编码及其文件扩展名，不包括优先级不高于 `identity` 的编码。
+    pub(crate) fn candidates(&self, headers: &HeaderMap) -> Vec<(&'static str, &'static str)> {
+        let accept = AcceptEncoding::from_headers(headers);
+
+        let mut candidates = [
+            ("br", "br", self.br),
+            ("gzip", "gz", self.gzip),
+            ("identity", "", true),
+        ]
+        .into_iter()
+        .filter(|(_, _, enabled)| *enabled)
+        .map(|(encoding, ext, _)| (encoding, ext, accept.quality(encoding)))
+        .filter(|(_, _, quality)| *quality > 0.0)
+        .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        candidates
+            .into_iter()
+            .take_while(|(encoding, _, _)| *encoding != "identity")
+            .map(|(encoding, ext, _)| (encoding, ext))
+            .collect()
+    }
+}
+
+/// 文件打开失败时，这些错误被视为文件不存在。
+pub(crate) fn is_not_found(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+    )
+}
+
+/// 解码请求路径并去掉空段和 `.`，返回以 `/` 分隔的相对路径，根目录为空字符串。
+///
+/// 包含 `..`、反斜杠、`:` 或 NUL 的路径不合法，返回 `None`。
+pub(crate) fn normalize_path(path: &str) -> Option<String> {
+    let path = percent_decode_str(path).decode_utf8().ok()?;
+
+    let mut normalized = String::with_capacity(path.len());
+    for segment in path.split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        if segment == ".." || segment.contains(['\\', ':', '\0']) {
+            return None;
+        }
+        if !normalized.is_empty() {
+            normalized.push('/');
+        }
+        normalized.push_str(segment);
+    }
+    Some(normalized)
+}
+
+/// 请求目录但路径不以 `/` 结尾时，返回相对于当前路径的重定向地址。
+///
+/// 使用相对地址是因为在 `Router::scope` 中无法得知被去掉的前缀。
+pub(crate) fn directory_redirect(uri: &Uri) -> Option<String> {
+    let name = uri
+        .path()
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())?;
+    Some(match uri.query() {
+        Some(query) => format!("./{name}/?{query}"),
+        None => format!("./{name}/"),
+    })
+}
+
+pub(crate) fn method_not_allowed() -> Response {
+    Response::builder()
+        .status(StatusCode::METHOD_NOT_ALLOWED)
+        .header(header::ALLOW, "GET, HEAD")
+        .body(BoxBody::default())
+        .unwrap()
+}
+
+pub(crate) fn not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(BoxBody::default())
+        .unwrap()
+}
+
+/// 返回 `path` 指向的文件，文件不存在或者不是普通文件时返回 `None`。
+///
+/// `Content-Type` 总是根据 `path` 推断，即使实际返回的是预压缩文件。
+pub(crate) async fn serve(
+    method: &Method,
+    headers: &HeaderMap,
+    path: &Path,
+    precompressed: Precompressed,
+) -> io::Result<Option<Response>> {
+    let Some((file, metadata, encoding)) = open(path, headers, precompressed).await? else {
+        return Ok(None);
+    };
+
+    let modified = metadata.modified().ok();
+    let etag = etag(modified, metadata.len(), encoding);
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+
+    let representation = Representation {
+        len: metadata.len(),
+        etag: &etag,
+        modified,
+        mime: mime.as_ref(),
+        encoding,
+        vary: precompressed.is_enabled(),
+    };
+    let (mut res, segments) = respond(method, headers, &representation);
+    if !segments.is_empty() {
+        *res.body_mut() = SegmentBody::from_file(file, segments).boxed();
+    }
+
+    Ok(Some(res))
+}
+
+/// 被选中的文件内容及其元数据。
+pub(crate) struct Representation<'a> {
+    pub(crate) len: u64,
+    pub(crate) etag: &'a str,
+    pub(crate) modified: Option<SystemTime>,
+    pub(crate) mime: &'a str,
+    pub(crate) encoding: Option<&'static str>,
+    /// 内容是否会随 `Accept-Encoding` 变化。
+    pub(crate) vary: bool,
+}
+
+/// 根据条件请求头和 `Range` 生成响应，返回的片段需要作为响应体输出。
+pub(crate) fn respond(
+    method: &Method,
+    headers: &HeaderMap,
+    representation: &Representation<'_>,
+) -> (Response, Vec<Segment>) {
+    let Representation {
+        len,
+        etag,
+        modified,
+        mime,
+        encoding,
+        vary,
+    } = *representation;
+
+    let mut res = Response::new(BoxBody::default());
+    let res_headers = res.headers_mut();
+    res_headers.insert(header::ETAG, HeaderValue::from_str(etag).unwrap());
+    if let Some(modified) = modified {
+        res_headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&httpdate::fmt_http_date(modified)).unwrap(),
+        );
+    }
+    if vary {
+        res_headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+
+    if let Some(status) = precondition(headers, etag, modified) {
+        *res.status_mut() = status;
+        return (res, Vec::new());
+    }
+
+    let res_headers = res.headers_mut();
+    res_headers.insert(header::CONTENT_TYPE, HeaderValue::from_str(mime).unwrap());
+    res_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Some(encoding) = encoding {
+        res_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+    }
+
+    let ranges = match header_str(headers, header::RANGE) {
+        Some(range)
+            if method == Method::GET
+                && encoding.is_none()
+                && if_range_matches(headers, etag, modified) =>
+        {
+            parse_range(range, len)
+        }
+        _ => ParsedRange::Ignored,
+    };
+
+    let segments = match ranges {
+        ParsedRange::Ignored => vec![Segment::Content(0..len)],
+        ParsedRange::Unsatisfiable => {
+            *res.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            let res_headers = res.headers_mut();
+            res_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{len}")).unwrap(),
+            );
+            res_headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("0"));
+            return (res, Vec::new());
+        }
+        ParsedRange::Satisfiable(ranges) if ranges.len() == 1 => {
+            let range = ranges.into_iter().next().unwrap();
+            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+            res.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&content_range(&range, len)).unwrap(),
+            );
+            vec![Segment::Content(range)]
+        }
+        ParsedRange::Satisfiable(ranges) => {
+            let boundary = boundary();
+            let mut segments = Vec::with_capacity(ranges.len() * 2 + 1);
+            for range in ranges {
+                let part = format!(
+                    "\r\n--{boundary}\r\n{}: {mime}\r\n{}: {}\r\n\r\n",
+                    header::CONTENT_TYPE,
+                    header::CONTENT_RANGE,
+                    content_range(&range, len),
+                );
+                segments.push(Segment::Bytes(part.into()));
+                segments.push(Segment::Content(range));
+            }
+            segments.push(Segment::Bytes(format!("\r\n--{boundary}--\r\n").into()));
+
+            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}"))
+                    .unwrap(),
+            );
+            segments
+        }
+    };
+
+    let body_len = segments.iter().map(Segment::len).sum::<u64>();
+    res.headers_mut()
+        .insert(header::CONTENT_LENGTH, HeaderValue::from(body_len));
+
+    if method == Method::HEAD {
+        return (res, Vec::new());
+    }
+    (res, segments)
+}
+
+async fn open(
+    path: &Path,
+    headers: &HeaderMap,
+    precompressed: Precompressed,
+) -> io::Result<Option<(File, Metadata, Option<&'static str>)>> {
+    // 部分内容总是基于原始文件计算。
+    if precompressed.is_enabled() && !headers.contains_key(header::RANGE) {
+        for (encoding, ext) in precompressed.candidates(headers) {
+            let mut sibling = path.as_os_str().to_owned();
+            sibling.push(".");
+            sibling.push(ext);
+            if let Some((file, metadata)) = open_file(Path::new(&sibling)).await? {
+                return Ok(Some((file, metadata, Some(encoding))));
+            }
+        }
+    }
+
+    Ok(open_file(path)
+        .await?
+        .map(|(file, metadata)| (file, metadata, None)))
+}
+
+async fn open_file(path: &Path) -> io::Result<Option<(File, Metadata)>> {
+    let file = match File::open(path).await {
+        Ok(file) => file,
+        Err(e) if is_not_found(&e) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let metadata = file.metadata().await?;
+    Ok(metadata.is_file().then_some((file, metadata)))
+}
+
+fn etag(modified: Option<SystemTime>, len: u64, encoding: Option<&str>) -> String {
+    let nanos = modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    match encoding {
+        Some(encoding) => format!("\"{nanos:x}-{len:x}-{encoding}\""),
+        None => format!("\"{nanos:x}-{len:x}\""),
+    }
+}
+
+/// 按照 RFC 9110 第 13.2.2 节的顺序检查条件请求头。
+fn precondition(
+    headers: &HeaderMap,
+    etag: &str,
+    modified: Option<SystemTime>,
+) -> Option<StatusCode> {
+    if let Some(value) = header_str(headers, header::IF_MATCH) {
+        if !etag_matches(value, etag, false) {
+            return Some(StatusCode::PRECONDITION_FAILED);
+        }
+    } else if let Some(since) = header_date(headers, header::IF_UNMODIFIED_SINCE) {
+        if modified.is_some_and(|modified| unix_secs(modified) > unix_secs(since)) {
+            return Some(StatusCode::PRECONDITION_FAILED);
+        }
+    }
+
+    if let Some(value) = header_str(headers, header::IF_NONE_MATCH) {
+        if etag_matches(value, etag, true) {
+            return Some(StatusCode::NOT_MODIFIED);
+        }
+    } else if let Some(since) = header_date(headers, header::IF_MODIFIED_SINCE) {
+        if modified.is_some_and(|modified| unix_secs(modified) <= unix_secs(since)) {
+            return Some(StatusCode::NOT_MODIFIED);
+        }
+    }
+
+    None
+}
+
+/// `If-Range` 只使用强比较，日期必须与 `Last-Modified` 完全相同。
+fn if_range_matches(headers: &HeaderMap, etag: &str, modified: Option<SystemTime>) -> bool {
+    let Some(value) = header_str(headers, header::IF_RANGE) else {
+        return true;
+    };
+    if value.starts_with('"') {
+        return value == etag;
+    }
+    match httpdate::parse_http_date(value) {
+        Ok(date) => modified.is_some_and(|modified| unix_secs(modified) == unix_secs(date)),
+        Err(_) => false,
+    }
+}
+
+fn etag_matches(value: &str, etag: &str, weak: bool) -> bool {
+    value.split(',').map(str::trim).any(|tag| {
+        if tag == "*" {
+            return true;
+        }
+        match tag.strip_prefix("W/") {
+            Some(tag) => weak && tag == etag,
+            None => tag == etag,
+        }
+    })
+}
+
+fn header_str(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn header_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    header_str(headers, name).and_then(|value| httpdate::parse_http_date(value).ok())
+}
+
+/// HTTP 日期只精确到秒。
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn content_range(range: &Range<u64>, len: u64) -> String {
+    format!("bytes {}-{}/{len}", range.start, range.end - 1)
+}
+
+fn boundary() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    format!(
+        "{nanos:08x}{:016x}",
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+pub(crate) enum Segment {
+    Bytes(Bytes),
+    /// 文件内容中的一段。
+    Content(Range<u64>),
+}
+
+impl Segment {
+    fn len(&self) -> u64 {
+        match self {
+            Segment::Bytes(bytes) => bytes.len() as u64,
+            Segment::Content(range) => range.end - range.start,
+        }
+    }
+}
+
+/// 依次输出各个片段的响应体。
+pub(crate) struct SegmentBody {
+    stream: BoxStream<'static, io::Result<Bytes>>,
+    remaining: u64,
+}
+
+impl SegmentBody {
+    /// 文件内容按块读取。
+    fn from_file(file: File, segments: Vec<Segment>) -> Self {
+        let len = segments.iter().map(Segment::len).sum();
+        let state = (file, 0, VecDeque::from(segments));
+        let stream = stream::try_unfold(state, |(mut file, mut pos, mut segments)| async move {
+            loop {
+                let chunk = match segments.front_mut() {
+                    None => return Ok(None),
+                    Some(Segment::Bytes(bytes)) => {
+                        let bytes = std::mem::take(bytes);
+                        segments.pop_front();
+                        bytes
+                    }
+                    Some(Segment::Content(range)) if range.is_empty() => {
+                        segments.pop_front();
+                        continue;
+                    }
+                    Some(Segment::Content(range)) => {
+                        if pos != range.start {
+                            pos = file.seek(SeekFrom::Start(range.start)).await?;
+                        }
+                        let mut buf = vec![0; (range.end - range.start).min(CHUNK_SIZE) as usize];
+                        let n = file.read(&mut buf).await?;
+                        if n == 0 {
+                            return Err(io::ErrorKind::UnexpectedEof.into());
+                        }
+                        buf.truncate(n);
+                        pos += n as u64;
+                        range.start += n as u64;
+                        buf.into()
+                    }
+                };
+                return Ok(Some((chunk, (file, pos, segments))));
+            }
+        });
+
+        Self {
+            stream: stream.boxed(),
+            remaining: len,
+        }
+    }
+
+    #[cfg(feature = "embed")]
+    pub(crate) fn from_static(contents: &'static [u8], segments: Vec<Segment>) -> Self {
+        let len = segments.iter().map(Segment::len).sum();
+        let chunks = segments.into_iter().map(|segment| match segment {
+            Segment::Bytes(bytes) => Ok(bytes),
+            Segment::Content(range) => Ok(Bytes::from_static(
+                &contents[range.start as usize..range.end as usize],
+            )),
+        });
+
+        Self {
+            stream: stream::iter(chunks).boxed(),
+            remaining: len,
+        }
+    }
+}
+
+impl Body for SegmentBody {
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let chunk = ready!(self.stream.poll_next_unpin(cx));
+        if let Some(Ok(data)) = &chunk {
+            self.remaining = self.remaining.saturating_sub(data.len() as u64);
+        }
+        Poll::Ready(chunk.map(|chunk| chunk.map(Frame::data)))
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_path;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_path("/").as_deref(), Some(""));
+        assert_eq!(normalize_path("/a/./b//c/").as_deref(), Some("a/b/c"));
+        assert_eq!(normalize_path("/a%20b").as_deref(), Some("a b"));
+
+        for path in [
+            "/../secret",
+            "/a/../../secret",
+            "/%2e%2e/secret",
+            "/%2E%2E%2fsecret",
+            "/a\\..\\secret",
+            "/a%5c..%5csecret",
+            "/c:/secret",
+            "/a%00b",
+            "/%ff",
+        ] {
+            assert_eq!(normalize_path(path), None, "{path}");
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! 静态文件服务。
+//!
+//! [`ServeFile`] 和 [`ServeDir`] 以流的方式返回文件内容，并支持：
+//!
+//! - `ETag` 和 `Last-Modified`，以及 `If-Match`、`If-None-Match`、`If-Modified-Since`、
+//!   `If-Unmodified-Since` 条件请求；
+//! - `Range` 和 `If-Range`，多个范围时返回 `multipart/byteranges`；
+//! - 根据扩展名推断 `Content-Type`；
+//! - 根据 `Accept-Encoding` 返回预先压缩好的 `.br`、`.gz` 文件。
+//!
+//! 只接受 `GET` 和 `HEAD` 请求，其他方法返回 `405 Method Not Allowed`。
+//!
+//! 启用 `embed` 特性后，可以使用 [`embed_dir!`] 将目录嵌入二进制文件。
+
+#[cfg(feature = "embed")]
+mod embedded;
+mod file;
+mod range;
+mod serve_dir;
+mod serve_file;
+
+pub use serve_dir::ServeDir;
+pub use serve_file::ServeFile;
+
+#[cfg(feature = "embed")]
+pub use embedded::{EmbeddedDir, EmbeddedFile};
+
+#[cfg(feature = "embed")]
+pub use echo_macros::embed_dir;
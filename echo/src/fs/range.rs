@@ -0,0 +1,89 @@
+use std::ops::Range;
+
+/// 最多支持的范围数量，超过时忽略 `Range` 头并返回完整内容。
+const MAX_RANGES: usize = 16;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ParsedRange {
+    /// 没有可用的范围，应返回完整内容。
+    Ignored,
+    /// 所有范围都超出了内容长度，应返回 `416 Range Not Satisfiable`。
+    Unsatisfiable,
+    Satisfiable(Vec<Range<u64>>),
+}
+
+/// 解析 `Range` 头，语法错误或不支持的单位会被忽略。
+pub(crate) fn parse_range(value: &str, len: u64) -> ParsedRange {
+    let Some(specs) = value.trim().strip_prefix("bytes=") else {
+        return ParsedRange::Ignored;
+    };
+
+    let mut ranges = Vec::new();
+    let mut specs = specs
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .peekable();
+    // 空的范围列表在语法上是无效的。
+    if specs.peek().is_none() {
+        return ParsedRange::Ignored;
+    }
+    for spec in specs {
+        let Some((start, end)) = spec.split_once('-') else {
+            return ParsedRange::Ignored;
+        };
+        let (start, end) = (start.trim(), end.trim());
+
+        let range = if start.is_empty() {
+            let Ok(suffix) = end.parse::<u64>() else {
+                return ParsedRange::Ignored;
+            };
+            (suffix > 0).then(|| len.saturating_sub(suffix)..len)
+        } else {
+            let Ok(start) = start.parse::<u64>() else {
+                return ParsedRange::Ignored;
+            };
+            let end = if end.is_empty() {
+                len
+            } else {
+                match end.parse::<u64>() {
+                    Ok(end) if end >= start => end.saturating_add(1).min(len),
+                    _ => return ParsedRange::Ignored,
+                }
+            };
+            (start < len).then_some(start..end)
+        };
+
+        ranges.extend(range.filter(|range| !range.is_empty()));
+    }
+
+    if ranges.len() > MAX_RANGES {
+        ParsedRange::Ignored
+    } else if ranges.is_empty() {
+        ParsedRange::Unsatisfiable
+    } else {
+        ParsedRange::Satisfiable(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_range, ParsedRange};
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_range("bytes=0-4, 10-, -3", 20),
+            ParsedRange::Satisfiable(vec![0..5, 10..20, 17..20])
+        );
+        assert_eq!(
+            parse_range("bytes=5-100, 0-0", 20),
+            ParsedRange::Satisfiable(vec![5..20, 0..1])
+        );
+        assert_eq!(parse_range("bytes=20-", 20), ParsedRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=5-1", 20), ParsedRange::Ignored);
+        assert_eq!(parse_range("items=0-1", 20), ParsedRange::Ignored);
+        assert_eq!(parse_range("bytes=", 20), ParsedRange::Ignored);
+        assert_eq!(parse_range("bytes= , ", 20), ParsedRange::Ignored);
+    }
+}
@@ -0,0 +1,252 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use echo_core::http::{HeaderMap, Method, Uri};
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+
+use super::file::{self, Precompressed};
+use crate::response::Redirect;
+
+/// 返回目录中文件的服务，请求路径相对于 `root` 解析。
+///
+/// 配合 [`Router::scope`](crate::route::Router::scope) 使用时，请求路径是去掉前缀后的剩余部分。
+/// 包含 `..`、反斜杠、`:` 或 NUL 的路径都被视为不存在，不会访问 `root` 之外的文件。
+///
+/// 请求目录时返回其中的 [`index_file`](ServeDir::index_file)；如果请求路径不以 `/` 结尾，
+/// 会先使用 `307 Temporary Redirect` 重定向到以 `/` 结尾的路径，以便页面中的相对链接正常工作。
+///
+/// # 例子
+///
+/// ```
+/// use echo::fs::ServeDir;
+/// use echo::route::Router;
+///
+/// let app = Router::new()
+///     .scope("/assets", ServeDir::new("assets").precompressed_gzip(true))
+///     .scope("/", ServeDir::new("dist").fallback("dist/index.html"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServeDir {
+    root: PathBuf,
+    index_file: String,
+    fallback: Option<PathBuf>,
+    precompressed: Precompressed,
+}
+
+impl ServeDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            index_file: "index.html".to_owned(),
+            fallback: None,
+            precompressed: Precompressed::default(),
+        }
+    }
+
+    /// 设置请求目录时返回的文件名，默认为 `index.html`。
+    pub fn index_file(mut self, name: impl Into<String>) -> Self {
+        self.index_file = name.into();
+        self
+    }
+
+    /// 设置文件不存在时返回的文件，状态码为 `200 OK`，适用于单页应用。
+    ///
+    /// `path` 不会相对于 `root` 解析。
+    pub fn fallback(mut self, path: impl Into<PathBuf>) -> Self {
+        self.fallback = Some(path.into());
+        self
+    }
+
+    /// 参见 [`ServeFile::precompressed_br`](super::ServeFile::precompressed_br)。
+    pub fn precompressed_br(mut self, enable: bool) -> Self {
+        self.precompressed.br = enable;
+        self
+    }
+
+    /// 参见 [`ServeFile::precompressed_gzip`](super::ServeFile::precompressed_gzip)。
+    pub fn precompressed_gzip(mut self, enable: bool) -> Self {
+        self.precompressed.gzip = enable;
+        self
+    }
+
+    /// 将请求路径解析为 `root` 下的文件路径，路径不合法时返回 `None`。
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let path = file::normalize_path(path)?;
+        let mut resolved = self.root.clone();
+        resolved.extend(path.split('/').filter(|segment| !segment.is_empty()));
+        Some(resolved)
+    }
+
+    async fn serve(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+    ) -> io::Result<Option<Response>> {
+        let Some(mut path) = self.resolve(uri.path()) else {
+            return Ok(None);
+        };
+
+        if is_dir(&path).await? {
+            if let Some(location) = file::directory_redirect(uri) {
+                return Ok(Some(Redirect::temporary(&location).into_response()));
+            }
+            path.push(&self.index_file);
+        }
+
+        file::serve(method, headers, &path, self.precompressed).await
+    }
+}
+
+impl<B> Service<Request<B>> for ServeDir {
+    type Response = Response;
+    type Error = io::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, io::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request<B>) -> Self::Future<'_> {
+        let (parts, _) = req.into_parts();
+
+        Box::pin(async move {
+            if parts.method != Method::GET && parts.method != Method::HEAD {
+                return Ok(file::method_not_allowed());
+            }
+
+            if let Some(res) = self
+                .serve(&parts.method, &parts.uri, &parts.headers)
+                .await?
+            {
+                return Ok(res);
+            }
+
+            let res = match &self.fallback {
+                Some(fallback) => {
+                    file::serve(&parts.method, &parts.headers, fallback, self.precompressed).await?
+                }
+                None => None,
+            };
+            Ok(res.unwrap_or_else(file::not_found))
+        })
+    }
+}
+
+async fn is_dir(path: &Path) -> io::Result<bool> {
+    match tokio::fs::metadata(path).await {
+        Ok(metadata) => Ok(metadata.is_dir()),
+        Err(e) if file::is_not_found(&e) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::http::{header, Request, StatusCode};
+    use echo_core::service::Service;
+    use echo_core::Response;
+
+    use super::ServeDir;
+
+    /// 为每个测试创建如下目录结构，返回 `public` 的路径：
+    ///
+    /// ```text
+    /// secret.txt
+    /// public/a.txt
+    /// public/index.html
+    /// public/docs/index.html
+    /// ```
+    fn root(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("echo-serve-dir-{}-{name}", std::process::id()));
+        let root = dir.join("public");
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(root.join("index.html"), "index").unwrap();
+        std::fs::write(root.join("docs/index.html"), "docs").unwrap();
+        root
+    }
+
+    async fn get(svc: &ServeDir, uri: &str) -> Response {
+        let req = Request::builder().uri(uri).body(()).unwrap();
+        svc.call(req).await.unwrap()
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn serve() {
+        let svc = ServeDir::new(root("serve"));
+        let res = get(&svc, "/a.txt").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res).await, "a");
+
+        let res = get(&svc, "/docs/./index.html").await;
+        assert_eq!(body(res).await, "docs");
+
+        let res = get(&svc, "/missing.txt").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn traversal() {
+        let svc = ServeDir::new(root("traversal"));
+        for uri in [
+            "/../secret.txt",
+            "/docs/../../secret.txt",
+            "/%2e%2e/secret.txt",
+            "/%2E%2E%2Fsecret.txt",
+            "/..%5csecret.txt",
+            "/docs%5c..%5c..%5csecret.txt",
+        ] {
+            let res = get(&svc, uri).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn index() {
+        let svc = ServeDir::new(root("index"));
+        let res = get(&svc, "/").await;
+        assert_eq!(body(res).await, "index");
+        let res = get(&svc, "/docs/").await;
+        assert_eq!(body(res).await, "docs");
+
+        let res = get(&svc, "/docs").await;
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(res.headers()[header::LOCATION], "./docs/");
+        let res = get(&svc, "/docs?page=2").await;
+        assert_eq!(res.headers()[header::LOCATION], "./docs/?page=2");
+
+        let svc = ServeDir::new(root("index")).index_file("a.txt");
+        let res = get(&svc, "/").await;
+        assert_eq!(body(res).await, "a");
+        let res = get(&svc, "/docs/").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let root = root("fallback");
+        let svc = ServeDir::new(&root).fallback(root.join("index.html"));
+        let res = get(&svc, "/app/settings").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html");
+        assert_eq!(body(res).await, "index");
+
+        // 不合法的路径也使用后备文件。
+        let res = get(&svc, "/%2e%2e/secret.txt").await;
+        assert_eq!(body(res).await, "index");
+
+        let res = get(&svc, "/a.txt").await;
+        assert_eq!(body(res).await, "a");
+    }
+}
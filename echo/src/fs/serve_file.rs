@@ -0,0 +1,328 @@
+use std::io;
+use std::path::PathBuf;
+
+use echo_core::http::Method;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+
+use super::file::{self, Precompressed};
+
+/// 返回单个文件的服务，文件不存在时返回 `404 Not Found`。
+///
+/// # 例子
+///
+/// ```
+/// use echo::fs::ServeFile;
+/// use echo::route::Router;
+///
+/// let app = Router::new().route("/favicon.ico", ServeFile::new("assets/favicon.ico"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServeFile {
+    path: PathBuf,
+    precompressed: Precompressed,
+}
+
+impl ServeFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            precompressed: Precompressed::default(),
+        }
+    }
+
+    /// 客户端接受 `br` 编码时，优先返回同目录下的 `<文件名>.br`。
+    pub fn precompressed_br(mut self, enable: bool) -> Self {
+        self.precompressed.br = enable;
+        self
+    }
+
+    /// 客户端接受 `gzip` 编码时，优先返回同目录下的 `<文件名>.gz`。
+    pub fn precompressed_gzip(mut self, enable: bool) -> Self {
+        self.precompressed.gzip = enable;
+        self
+    }
+}
+
+impl<B> Service<Request<B>> for ServeFile {
+    type Response = Response;
+    type Error = io::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, io::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request<B>) -> Self::Future<'_> {
+        let (parts, _) = req.into_parts();
+
+        Box::pin(async move {
+            if parts.method != Method::GET && parts.method != Method::HEAD {
+                return Ok(file::method_not_allowed());
+            }
+            let res = file::serve(
+                &parts.method,
+                &parts.headers,
+                &self.path,
+                self.precompressed,
+            )
+            .await?;
+            Ok(res.unwrap_or_else(file::not_found))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::http::header::{self, HeaderValue};
+    use echo_core::http::{Method, Request, StatusCode};
+    use echo_core::service::Service;
+    use echo_core::Response;
+
+    use super::ServeFile;
+
+    const CONTENTS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+    /// 在临时目录中创建 `name` 及其预压缩文件。
+    fn file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("echo-serve-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, CONTENTS).unwrap();
+        std::fs::write(dir.join(format!("{name}.gz")), "gzip").unwrap();
+        std::fs::write(dir.join(format!("{name}.br")), "br").unwrap();
+        path
+    }
+
+    async fn call(svc: &ServeFile, method: Method, headers: &[(&str, &str)]) -> Response {
+        let mut req = Request::builder().method(method);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        svc.call(req.body(()).unwrap()).await.unwrap()
+    }
+
+    async fn get(svc: &ServeFile, headers: &[(&str, &str)]) -> Response {
+        call(svc, Method::GET, headers).await
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    fn header(res: &Response, name: header::HeaderName) -> &str {
+        res.headers()[name].to_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn serve() {
+        let svc = ServeFile::new(file("serve.txt"));
+        let res = get(&svc, &[]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(header(&res, header::CONTENT_TYPE), "text/plain");
+        assert_eq!(header(&res, header::CONTENT_LENGTH), "26");
+        assert_eq!(header(&res, header::ACCEPT_RANGES), "bytes");
+        assert!(!res.headers().contains_key(header::VARY));
+        assert_eq!(body(res).await, CONTENTS);
+
+        let missing = ServeFile::new(file("serve.txt").with_extension("missing"));
+        let res = get(&missing, &[]).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = call(&svc, Method::POST, &[]).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(header(&res, header::ALLOW), "GET, HEAD");
+    }
+
+    #[tokio::test]
+    async fn head() {
+        let svc = ServeFile::new(file("head.txt"));
+        let res = call(&svc, Method::HEAD, &[]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(header(&res, header::CONTENT_LENGTH), "26");
+        assert!(res.headers().contains_key(header::ETAG));
+        assert!(body(res).await.is_empty());
+
+        // HEAD 请求忽略 `Range`。
+        let res = call(&svc, Method::HEAD, &[("range", "bytes=0-4")]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(body(res).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn preconditions() {
+        let svc = ServeFile::new(file("preconditions.txt"));
+        let res = get(&svc, &[]).await;
+        let etag = header(&res, header::ETAG).to_owned();
+        let modified = header(&res, header::LAST_MODIFIED).to_owned();
+        let weak = format!("W/{etag}");
+        let other = "\"other\"";
+        let epoch = "Thu, 01 Jan 1970 00:00:00 GMT";
+
+        let cases = [
+            (
+                vec![("if-none-match", etag.as_str())],
+                StatusCode::NOT_MODIFIED,
+            ),
+            (
+                vec![("if-none-match", weak.as_str())],
+                StatusCode::NOT_MODIFIED,
+            ),
+            (vec![("if-none-match", other)], StatusCode::OK),
+            (
+                vec![("if-modified-since", modified.as_str())],
+                StatusCode::NOT_MODIFIED,
+            ),
+            (vec![("if-modified-since", epoch)], StatusCode::OK),
+            (vec![("if-match", etag.as_str())], StatusCode::OK),
+            (vec![("if-match", "*")], StatusCode::OK),
+            (
+                vec![("if-match", weak.as_str())],
+                StatusCode::PRECONDITION_FAILED,
+            ),
+            (vec![("if-match", other)], StatusCode::PRECONDITION_FAILED),
+            (
+                vec![("if-unmodified-since", epoch)],
+                StatusCode::PRECONDITION_FAILED,
+            ),
+            (
+                vec![("if-unmodified-since", modified.as_str())],
+                StatusCode::OK,
+            ),
+            // `If-None-Match` 存在时忽略 `If-Modified-Since`。
+            (
+                vec![
+                    ("if-none-match", other),
+                    ("if-modified-since", modified.as_str()),
+                ],
+                StatusCode::OK,
+            ),
+            (
+                vec![("if-match", other), ("if-none-match", etag.as_str())],
+                StatusCode::PRECONDITION_FAILED,
+            ),
+        ];
+        for (headers, status) in cases {
+            let res = get(&svc, &headers).await;
+            assert_eq!(res.status(), status, "{headers:?}");
+            assert_eq!(header(&res, header::ETAG), etag);
+            if status != StatusCode::OK {
+                assert!(body(res).await.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn range() {
+        let svc = ServeFile::new(file("range.txt"));
+        let res = get(&svc, &[("range", "bytes=0-4")]).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(header(&res, header::CONTENT_RANGE), "bytes 0-4/26");
+        assert_eq!(header(&res, header::CONTENT_LENGTH), "5");
+        assert_eq!(body(res).await, "abcde");
+
+        let res = get(&svc, &[("range", "bytes=-3")]).await;
+        assert_eq!(body(res).await, "xyz");
+
+        let res = get(&svc, &[("range", "bytes=26-")]).await;
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(header(&res, header::CONTENT_RANGE), "bytes */26");
+
+        // 语法错误的 `Range` 被忽略。
+        let res = get(&svc, &[("range", "bytes=")]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res).await, CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn if_range() {
+        let svc = ServeFile::new(file("if-range.txt"));
+        let res = get(&svc, &[]).await;
+        let etag = header(&res, header::ETAG).to_owned();
+        let modified = header(&res, header::LAST_MODIFIED).to_owned();
+
+        for value in [etag.as_str(), modified.as_str()] {
+            let res = get(&svc, &[("range", "bytes=1-2"), ("if-range", value)]).await;
+            assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT, "{value}");
+            assert_eq!(body(res).await, "bc");
+        }
+
+        // 不匹配时返回完整内容。
+        let weak = format!("W/{etag}");
+        for value in ["\"other\"", weak.as_str(), "Thu, 01 Jan 1970 00:00:00 GMT"] {
+            let res = get(&svc, &[("range", "bytes=1-2"), ("if-range", value)]).await;
+            assert_eq!(res.status(), StatusCode::OK, "{value}");
+            assert_eq!(body(res).await, CONTENTS);
+        }
+    }
+
+    #[tokio::test]
+    async fn multipart() {
+        let svc = ServeFile::new(file("multipart.txt"));
+        let res = get(&svc, &[("range", "bytes=0-1, 24-")]).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(!res.headers().contains_key(header::CONTENT_RANGE));
+
+        let content_type = header(&res, header::CONTENT_TYPE);
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap()
+            .to_owned();
+        let len = header(&res, header::CONTENT_LENGTH)
+            .parse::<usize>()
+            .unwrap();
+
+        let expected = format!(
+            "\r\n--{boundary}\r\ncontent-type: text/plain\r\ncontent-range: bytes 0-1/26\r\n\r\nab\
+             \r\n--{boundary}\r\ncontent-type: text/plain\r\ncontent-range: bytes 24-25/26\r\n\r\nyz\
+             \r\n--{boundary}--\r\n"
+        );
+        let body = body(res).await;
+        assert_eq!(body, expected);
+        assert_eq!(body.len(), len);
+    }
+
+    #[tokio::test]
+    async fn precompressed() {
+        let svc = ServeFile::new(file("precompressed.txt"))
+            .precompressed_gzip(true)
+            .precompressed_br(true);
+
+        let cases = [
+            ("gzip", Some("gzip"), "gzip"),
+            ("gzip, br", Some("br"), "br"),
+            ("br;q=0.5, gzip", Some("gzip"), "gzip"),
+            ("identity, gzip;q=0.5", None, CONTENTS),
+            ("deflate", None, CONTENTS),
+        ];
+        for (accept, encoding, contents) in cases {
+            let res = get(&svc, &[("accept-encoding", accept)]).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(
+                res.headers().get(header::CONTENT_ENCODING),
+                encoding.map(HeaderValue::from_static).as_ref(),
+                "{accept}"
+            );
+            // 内容类型总是根据原始文件推断。
+            assert_eq!(header(&res, header::CONTENT_TYPE), "text/plain");
+            assert_eq!(header(&res, header::VARY), "accept-encoding");
+            assert_eq!(body(res).await, contents);
+        }
+
+        // 部分内容总是基于原始文件计算。
+        let res = get(&svc, &[("accept-encoding", "gzip"), ("range", "bytes=0-1")]).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(body(res).await, "ab");
+
+        // 预压缩文件有自己的 ETag。
+        let gzip = get(&svc, &[("accept-encoding", "gzip")]).await;
+        let identity = get(&svc, &[]).await;
+        assert_ne!(
+            gzip.headers()[header::ETAG],
+            identity.headers()[header::ETAG]
+        );
+    }
+}
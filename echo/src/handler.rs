@@ -0,0 +1,260 @@
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+
+use echo_core::body::DataTooLarge;
+use echo_core::response::{IntoResponse, IntoResponseResult};
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+use crate::extract::{FromRequest, FromRequestParts};
+
+/// 将参数均可从请求中提取的异步函数转换为服务。
+///
+/// 除最后一个参数需实现 [`FromRequest`] 外，其余参数都需实现 [`FromRequestParts`]。
+///
+/// # 例子
+///
+/// ```
+/// use echo::extract::{Json, Path};
+/// use echo::handler::handler;
+/// use echo::route::Router;
+///
+/// async fn update(Path(id): Path<u32>, Json(name): Json<String>) -> String {
+///     format!("{id}: {name}")
+/// }
+///
+/// let router = Router::new().route("/users/:id", handler(update));
+/// ```
+#[inline]
+pub fn handler<F, T>(f: F) -> HandlerService<F, T>
+where
+    F: Handler<T>,
+{
+    HandlerService {
+        f,
+        _marker: PhantomData,
+    }
+}
+
+pub trait Handler<T>: Send + Sync {
+    fn call(&self, req: Request) -> BoxFuture<'_, Result<Response, BoxError>>;
+}
+
+pub struct HandlerService<F, T> {
+    f: F,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<F, T> Service<Request> for HandlerService<F, T>
+where
+    F: Handler<T>,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    #[inline]
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        self.f.call(req)
+    }
+}
+
+impl<F, T> Clone for HandlerService<F, T>
+where
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, T> Copy for HandlerService<F, T> where F: Copy {}
+
+impl<F, T> fmt::Debug for HandlerService<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerService")
+            .field("f", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<F, Fut> Handler<()> for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future + Send,
+    Fut::Output: IntoResponseResult,
+{
+    fn call(&self, _: Request) -> BoxFuture<'_, Result<Response, BoxError>> {
+        Box::pin(async move { self().await.into_response_result() })
+    }
+}
+
+/// 请求体超过大小限制时返回 `413 Payload Too Large`，其它提取错误原样返回。
+///
+/// 供 `#[route]` 宏生成的代码使用。
+#[doc(hidden)]
+pub fn rejection(e: BoxError) -> Result<Response, BoxError> {
+    if crate::util::find_source::<DataTooLarge>(&*e).is_some() {
+        Ok(DataTooLarge.into_response())
+    } else {
+        Err(e)
+    }
+}
+
+macro_rules! impl_handler {
+    ($($ty:ident),* ; $last:ident) => {
+        #[allow(non_snake_case)]
+        impl<F, Fut, $($ty,)* $last> Handler<($($ty,)* $last,)> for F
+        where
+            F: Fn($($ty,)* $last) -> Fut + Send + Sync,
+            Fut: Future + Send,
+            Fut::Output: IntoResponseResult,
+            $(
+                $ty: FromRequestParts + Send,
+                for<'a> $ty::Future<'a>: Send,
+            )*
+            $last: FromRequest + Send,
+            $last::Future: Send,
+        {
+            fn call(&self, req: Request) -> BoxFuture<'_, Result<Response, BoxError>> {
+                Box::pin(async move {
+                    #[allow(unused_mut)]
+                    let (mut parts, body) = req.into_parts();
+                    $(
+                        let $ty = match $ty::from_request_parts(&mut parts).await {
+                            Ok(value) => value,
+                            Err(e) => return rejection(e.into()),
+                        };
+                    )*
+                    let $last = match $last::from_request(Request::from_parts(parts, body)).await {
+                        Ok(value) => value,
+                        Err(e) => return rejection(e.into()),
+                    };
+                    self($($ty,)* $last).await.into_response_result()
+                })
+            }
+        }
+    };
+}
+
+impl_handler!(; T1);
+impl_handler!(T1; T2);
+impl_handler!(T1, T2; T3);
+impl_handler!(T1, T2, T3; T4);
+impl_handler!(T1, T2, T3, T4; T5);
+impl_handler!(T1, T2, T3, T4, T5; T6);
+impl_handler!(T1, T2, T3, T4, T5, T6; T7);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7; T8);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7, T8; T9);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9; T10);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10; T11);
+impl_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11; T12);
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    use echo_core::body::{BodyExt, BoxBody, Bytes};
+    use echo_core::http::request::Parts;
+    use echo_core::http::{header, Method, StatusCode};
+    use echo_core::service::Service;
+    use echo_core::Request;
+
+    use super::handler;
+    use crate::extract::{Extension, ExtractExtensionError, FromRequestParts, Json};
+    use crate::middleware::DEFAULT_BODY_LIMIT;
+
+    // 记录提取顺序的提取器。
+    struct Step(usize);
+
+    impl FromRequestParts for Step {
+        type Error = Infallible;
+        type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+        fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+            let step = parts.extensions.get::<usize>().copied().unwrap_or(0);
+            parts.extensions.insert(step + 1);
+            ready(Ok(Step(step)))
+        }
+    }
+
+    async fn body(res: echo_core::Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn extractors() {
+        async fn f(
+            method: Method,
+            a: Step,
+            b: Step,
+            Extension(n): Extension<usize>,
+            body: Bytes,
+        ) -> String {
+            format!(
+                "{method} {} {} {n} {}",
+                a.0,
+                b.0,
+                String::from_utf8_lossy(&body)
+            )
+        }
+
+        let mut req = Request::new(BoxBody::new("hello"));
+        *req.method_mut() = Method::POST;
+        let res = handler(f).call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body(res).await, "POST 0 1 2 hello");
+    }
+
+    #[tokio::test]
+    async fn option() {
+        async fn f(ext: Option<Extension<u32>>) -> String {
+            format!("{:?}", ext.map(|Extension(n)| n))
+        }
+
+        let res = handler(f).call(Request::default()).await.unwrap();
+        assert_eq!(body(res).await, "None");
+
+        let mut req = Request::default();
+        req.extensions_mut().insert(7u32);
+        let res = handler(f).call(req).await.unwrap();
+        assert_eq!(body(res).await, "Some(7)");
+    }
+
+    #[tokio::test]
+    async fn error() {
+        async fn f(_: Extension<u32>, _: Bytes) {}
+
+        let e = handler(f).call(Request::default()).await.unwrap_err();
+        assert!(e.is::<ExtractExtensionError>());
+    }
+
+    #[tokio::test]
+    async fn too_large() {
+        async fn bytes(body: Bytes) -> String {
+            body.len().to_string()
+        }
+        async fn json(Json(s): Json<String>) -> String {
+            s
+        }
+
+        let req = || {
+            Request::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(BoxBody::new(vec![b' '; DEFAULT_BODY_LIMIT + 1]))
+                .unwrap()
+        };
+        let res = handler(bytes).call(req()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let res = handler(json).call(req()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -1,5 +1,4 @@
 #![forbid(unsafe_code)]
-#![deny(private_in_public)]
 #![deny(unreachable_pub)]
 #![warn(missing_debug_implementations)]
 
@@ -8,15 +7,25 @@ pub use echo_core::*;
 mod util;
 
 pub mod extract;
+pub mod handler;
 pub mod middleware;
 pub mod response;
 pub mod route;
 
+#[cfg(feature = "cookie")]
+pub mod cookie;
+
+#[cfg(feature = "fs")]
+pub mod fs;
+
 #[cfg(feature = "macros")]
 pub use echo_macros::route;
 
 #[cfg(feature = "server")]
 pub mod server;
 
+#[cfg(feature = "typed-header")]
+pub use headers;
+
 #[cfg(feature = "ws")]
 pub mod ws;
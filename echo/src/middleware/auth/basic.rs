@@ -0,0 +1,98 @@
+use std::fmt;
+use std::future::Future;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use echo_core::service::future::BoxFuture;
+
+use super::{AuthError, AuthScheme};
+
+/// Basic 认证中的用户名和密码。
+#[derive(Clone, PartialEq, Eq)]
+pub struct BasicCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl BasicCredentials {
+    fn decode(credentials: &str) -> Option<Self> {
+        let decoded = STANDARD.decode(credentials).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some(Self {
+            username: username.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+}
+
+impl fmt::Debug for BasicCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicCredentials")
+            .field("username", &self.username)
+            .field("password", &"..")
+            .finish()
+    }
+}
+
+/// Basic 认证方案，参见 [`basic_auth`](super::basic_auth)。
+#[derive(Clone, Copy)]
+pub struct Basic<F> {
+    validate: F,
+}
+
+impl<F> Basic<F> {
+    pub fn new(validate: F) -> Self {
+        Self { validate }
+    }
+}
+
+impl<F, Fut, P> AuthScheme for Basic<F>
+where
+    F: Fn(BasicCredentials) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<P>> + Send + 'static,
+    P: Clone + Send + Sync + 'static,
+{
+    type Principal = P;
+
+    fn name(&self) -> &'static str {
+        "Basic"
+    }
+
+    fn authenticate<'a>(&'a self, credentials: &'a str) -> BoxFuture<'a, Result<P, AuthError>> {
+        let credentials = BasicCredentials::decode(credentials);
+        Box::pin(async move {
+            let credentials = credentials.ok_or(AuthError::InvalidCredentials)?;
+            (self.validate)(credentials)
+                .await
+                .ok_or(AuthError::InvalidCredentials)
+        })
+    }
+}
+
+impl<F> fmt::Debug for Basic<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Basic")
+            .field("validate", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let credentials = BasicCredentials::decode("QWxhZGRpbjpvcGVuIHNlc2FtZQ==").unwrap();
+        assert_eq!(credentials.username, "Aladdin");
+        assert_eq!(credentials.password, "open sesame");
+
+        let credentials = BasicCredentials::decode("dXNlcjpwYTpzcw==").unwrap();
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password, "pa:ss");
+
+        assert!(BasicCredentials::decode("dXNlcg==").is_none());
+        assert!(BasicCredentials::decode("not base64").is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+use std::future::Future;
+
+use echo_core::service::future::BoxFuture;
+
+use super::{AuthError, AuthScheme};
+
+/// Bearer 令牌认证方案，参见 [`bearer_auth`](super::bearer_auth)。
+#[derive(Clone, Copy)]
+pub struct Bearer<F> {
+    validate: F,
+}
+
+impl<F> Bearer<F> {
+    pub fn new(validate: F) -> Self {
+        Self { validate }
+    }
+}
+
+impl<F, Fut, P> AuthScheme for Bearer<F>
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<P>> + Send + 'static,
+    P: Clone + Send + Sync + 'static,
+{
+    type Principal = P;
+
+    fn name(&self) -> &'static str {
+        "Bearer"
+    }
+
+    fn authenticate<'a>(&'a self, credentials: &'a str) -> BoxFuture<'a, Result<P, AuthError>> {
+        let fut = (self.validate)(credentials.to_owned());
+        Box::pin(async move {
+            fut.await
+                .ok_or_else(|| AuthError::InvalidToken("the token is not valid".into()))
+        })
+    }
+}
+
+impl<F> fmt::Debug for Bearer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bearer")
+            .field("validate", &std::any::type_name::<F>())
+            .finish()
+    }
+}
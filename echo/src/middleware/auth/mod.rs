@@ -0,0 +1,421 @@
+mod basic;
+mod bearer;
+#[cfg(feature = "jwt")]
+mod jwt;
+
+pub use basic::{Basic, BasicCredentials};
+pub use bearer::Bearer;
+#[cfg(feature = "jwt")]
+pub use jwt::{JwtKeyError, JwtValidator};
+
+use std::borrow::Cow;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use echo_core::http::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+/// 使用 Basic 认证。
+///
+/// `validate` 接收请求中的用户名和密码，返回 `Some` 时认证成功，其中的值作为认证主体保存在请求扩展中，
+/// 可以通过 [`Extension`](crate::extract::Extension) 提取。比较密码时应当使用常数时间的算法。
+///
+/// 认证失败时返回 `401 Unauthorized`，并通过 `WWW-Authenticate` 头要求客户端认证。
+///
+/// # 例子
+///
+/// ```
+/// use echo::extract::Extension;
+/// use echo::handler::handler;
+/// use echo::middleware::{basic_auth, BasicCredentials};
+/// use echo::route::{get, Router};
+/// use echo::service::ServiceExt;
+///
+/// #[derive(Clone)]
+/// struct User(String);
+///
+/// async fn index(Extension(user): Extension<User>) -> String {
+///     format!("hello, {}", user.0)
+/// }
+///
+/// let app = Router::new().route("/", get(handler(index))).with(
+///     basic_auth(|credentials: BasicCredentials| async move {
+///         (credentials.username == "admin" && credentials.password == "secret")
+///             .then(|| User(credentials.username))
+///     })
+///     .realm("admin"),
+/// );
+/// ```
+pub fn basic_auth<F, Fut, P>(validate: F) -> AuthMiddleware<Basic<F>>
+where
+    F: Fn(BasicCredentials) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<P>> + Send + 'static,
+    P: Clone + Send + Sync + 'static,
+{
+    AuthMiddleware::new(Basic::new(validate))
+}
+
+/// 使用 Bearer 令牌认证。
+///
+/// `validate` 接收请求中的令牌，返回 `Some` 时认证成功，其中的值作为认证主体保存在请求扩展中。
+/// 验证 JWT 时可以使用 [`jwt_auth`]。
+///
+/// 认证失败时返回 `401 Unauthorized`，令牌无效时 `WWW-Authenticate` 头中包含
+/// `error="invalid_token"`，但不包含具体的原因。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::bearer_auth;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// #[derive(Clone)]
+/// struct ApiClient(&'static str);
+///
+/// let app = Router::new().with(bearer_auth(|token: String| async move {
+///     (token == "token-for-ci").then_some(ApiClient("ci"))
+/// }));
+/// ```
+pub fn bearer_auth<F, Fut, P>(validate: F) -> AuthMiddleware<Bearer<F>>
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<P>> + Send + 'static,
+    P: Clone + Send + Sync + 'static,
+{
+    AuthMiddleware::new(Bearer::new(validate))
+}
+
+/// 使用 JWT 作为 Bearer 令牌认证，验证通过后将令牌的声明保存在请求扩展中。
+///
+/// # 例子
+///
+/// ```
+/// use echo::extract::Extension;
+/// use echo::handler::handler;
+/// use echo::middleware::{jwt_auth, JwtValidator};
+/// use echo::route::{get, Router};
+/// use echo::service::ServiceExt;
+/// use serde::Deserialize;
+///
+/// #[derive(Clone, Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// async fn index(Extension(claims): Extension<Claims>) -> String {
+///     format!("hello, {}", claims.sub)
+/// }
+///
+/// let validator = JwtValidator::hs256(b"secret")
+///     .issuer(["https://auth.example.com"])
+///     .audience(["api"])
+///     .claims::<Claims>();
+///
+/// let app = Router::new()
+///     .route("/", get(handler(index)))
+///     .with(jwt_auth(validator));
+/// ```
+#[cfg(feature = "jwt")]
+pub fn jwt_auth<C>(validator: JwtValidator<C>) -> AuthMiddleware<JwtValidator<C>>
+where
+    C: serde::de::DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    AuthMiddleware::new(validator)
+}
+
+/// 认证方案，解析 `Authorization` 头中的凭据并得到认证主体。
+pub trait AuthScheme: Send + Sync + 'static {
+    type Principal: Clone + Send + Sync + 'static;
+
+    /// 方案的名称，例如 `Basic`，比较时不区分大小写。
+    fn name(&self) -> &'static str;
+
+    /// 验证 `Authorization` 头中方案名称之后的凭据。
+    fn authenticate<'a>(
+        &'a self,
+        credentials: &'a str,
+    ) -> BoxFuture<'a, Result<Self::Principal, AuthError>>;
+}
+
+/// 认证错误。
+#[derive(Debug)]
+pub enum AuthError {
+    /// 请求没有使用该方案提供凭据。
+    MissingCredentials,
+    /// 凭据格式错误或者不正确。
+    InvalidCredentials,
+    /// 令牌无效，例如签名错误或者已过期。
+    InvalidToken(BoxError),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => f.write_str("missing credentials"),
+            AuthError::InvalidCredentials => f.write_str("invalid credentials"),
+            AuthError::InvalidToken(e) => write!(f, "invalid token: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuthError::InvalidToken(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+#[derive(Debug)]
+pub struct AuthMiddleware<A> {
+    scheme: Arc<A>,
+    realm: Cow<'static, str>,
+}
+
+impl<A> AuthMiddleware<A> {
+    pub fn new(scheme: A) -> Self {
+        Self {
+            scheme: Arc::new(scheme),
+            realm: Cow::Borrowed("Restricted"),
+        }
+    }
+
+    /// 设置 `WWW-Authenticate` 头中的 `realm`，默认为 `Restricted`。
+    pub fn realm(mut self, realm: impl Into<Cow<'static, str>>) -> Self {
+        self.realm = realm.into();
+        self
+    }
+}
+
+impl<A> Clone for AuthMiddleware<A> {
+    fn clone(&self) -> Self {
+        Self {
+            scheme: self.scheme.clone(),
+            realm: self.realm.clone(),
+        }
+    }
+}
+
+impl<S, A> Middleware<S> for AuthMiddleware<A> {
+    type Service = Auth<S, A>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Auth {
+            service,
+            scheme: self.scheme,
+            realm: self.realm,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Auth<S, A> {
+    service: S,
+    scheme: Arc<A>,
+    realm: Cow<'static, str>,
+}
+
+impl<S, A> Clone for Auth<S, A>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            scheme: self.scheme.clone(),
+            realm: self.realm.clone(),
+        }
+    }
+}
+
+impl<S, A> Auth<S, A>
+where
+    A: AuthScheme,
+{
+    fn credentials(&self, req: &Request) -> Option<String> {
+        let value = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+        let (name, credentials) = value.split_once(' ')?;
+        name.eq_ignore_ascii_case(self.scheme.name())
+            .then(|| credentials.trim().to_owned())
+    }
+
+    fn challenge(&self, error: &AuthError) -> Response {
+        let mut challenge = format!("{} realm=\"{}\"", self.scheme.name(), escape(&self.realm));
+        // 不返回具体的原因，避免泄露验证的细节。
+        if let AuthError::InvalidToken(_) = error {
+            challenge.push_str(
+                ", error=\"invalid_token\", error_description=\"the access token is invalid\"",
+            );
+        }
+
+        let mut res = StatusCode::UNAUTHORIZED.into_response();
+        if let Ok(value) = HeaderValue::try_from(challenge) {
+            res.headers_mut().insert(WWW_AUTHENTICATE, value);
+        }
+        res
+    }
+}
+
+impl<S, A> Service<Request> for Auth<S, A>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+    A: AuthScheme,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request) -> Self::Future<'_> {
+        Box::pin(async move {
+            let result = match self.credentials(&req) {
+                Some(credentials) => self.scheme.authenticate(&credentials).await,
+                None => Err(AuthError::MissingCredentials),
+            };
+
+            match result {
+                Ok(principal) => {
+                    req.extensions_mut().insert(principal);
+                    self.service.call(req).await
+                }
+                Err(e) => Ok(self.challenge(&e)),
+            }
+        })
+    }
+}
+
+/// 转义 `quoted-string` 中的引号和反斜杠，并去掉控制字符。
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars().filter(|c| !c.is_control()) {
+        if c == '"' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use echo_core::service::service_fn;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct User(&'static str);
+
+    async fn index(req: Request) -> Result<Response, Infallible> {
+        let user = req.extensions().get::<User>().unwrap();
+        Ok(user.0.into_response())
+    }
+
+    fn request(authorization: Option<&str>) -> Request {
+        let mut req = Request::default();
+        if let Some(value) = authorization {
+            req.headers_mut()
+                .insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        }
+        req
+    }
+
+    #[tokio::test]
+    async fn basic() {
+        let svc = basic_auth(|credentials: BasicCredentials| async move {
+            (credentials.username == "admin" && credentials.password == "secret")
+                .then_some(User("admin"))
+        })
+        .realm("a \"quoted\" realm")
+        .transform(service_fn(index));
+
+        // admin:secret
+        let res = svc.call(request(Some("basic YWRtaW46c2VjcmV0"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // admin:wrong
+        for authorization in [None, Some("Basic YWRtaW46d3Jvbmc="), Some("Bearer token")] {
+            let res = svc.call(request(authorization)).await.unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                res.headers()[WWW_AUTHENTICATE],
+                r#"Basic realm="a \"quoted\" realm""#
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn bearer() {
+        let svc = bearer_auth(|token: String| async move {
+            (token == "valid").then_some(User("client"))
+        })
+        .transform(service_fn(index));
+
+        let res = svc.call(request(Some("Bearer valid"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = svc.call(request(None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res.headers()[WWW_AUTHENTICATE], r#"Bearer realm="Restricted""#);
+
+        let res = svc.call(request(Some("Bearer invalid"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers()[WWW_AUTHENTICATE],
+            r#"Bearer realm="Restricted", error="invalid_token", error_description="the access token is invalid""#
+        );
+    }
+
+    #[cfg(feature = "jwt")]
+    #[tokio::test]
+    async fn jwt() {
+        use jsonwebtoken::{EncodingKey, Header};
+        use serde_json::{json, Value};
+
+        let svc = jwt_auth(JwtValidator::hs256(b"secret")).transform(service_fn(
+            |req: Request| async move {
+                let claims = req.extensions().get::<Value>().unwrap();
+                Ok::<_, Infallible>(claims["sub"].to_string().into_response())
+            },
+        ));
+
+        let token = |exp: i64| {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let exp = now as i64 + exp;
+            let claims = json!({"sub": "alice", "exp": exp});
+            let key = EncodingKey::from_secret(b"secret");
+            jsonwebtoken::encode(&Header::default(), &claims, &key).unwrap()
+        };
+
+        let authorization = format!("Bearer {}", token(60));
+        let res = svc.call(request(Some(&authorization))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // 过期的令牌不会在响应中透露具体的原因。
+        let authorization = format!("Bearer {}", token(-120));
+        let res = svc.call(request(Some(&authorization))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let challenge = res.headers()[WWW_AUTHENTICATE].to_str().unwrap();
+        assert!(challenge.ends_with(r#"error_description="the access token is invalid""#));
+    }
+}
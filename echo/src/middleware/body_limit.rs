@@ -0,0 +1,169 @@
+use echo_core::body::DataTooLarge;
+use echo_core::http::Extensions;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+/// 未配置时 [`bytes`](crate::extract::bytes) 等提取器允许读取的最大请求体大小，为 2 MiB。
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// 设置 [`bytes`](crate::extract::bytes)、[`json`](crate::extract::json)、
+/// [`form`](crate::extract::form) 等提取器允许读取的最大请求体大小（字节）。
+///
+/// 中间件既可以作用于整个路由器，也可以作用于单个路由，内层的设置会覆盖外层的设置。
+///
+/// 请求体超过限制时，[`handler`](crate::handler::handler) 的提取器总是返回
+/// `413 Payload Too Large` 响应，无论是否使用了这个中间件。中间件还会将内部服务返回的
+/// [`DataTooLarge`] 错误转换为同样的响应，例如直接调用 [`bytes`](crate::extract::bytes) 的服务。
+///
+/// # 例子
+///
+/// ```
+/// use echo::handler::handler;
+/// use echo::middleware::body_limit;
+/// use echo::route::{post, Router};
+/// use echo::service::ServiceExt;
+///
+/// async fn upload(body: echo::body::Bytes) -> String {
+///     body.len().to_string()
+/// }
+///
+/// let app = Router::new()
+///     .route("/upload", post(handler(upload)).with(body_limit(64 * 1024 * 1024)))
+///     .with(body_limit(1024 * 1024));
+/// ```
+#[inline]
+pub fn body_limit(limit: usize) -> BodyLimitMiddleware {
+    BodyLimitMiddleware::new(Some(limit))
+}
+
+/// 取消请求体大小的限制。
+#[inline]
+pub fn disable_body_limit() -> BodyLimitMiddleware {
+    BodyLimitMiddleware::new(None)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimitMiddleware {
+    limit: Option<usize>,
+}
+
+impl BodyLimitMiddleware {
+    #[inline]
+    pub fn new(limit: Option<usize>) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Middleware<S> for BodyLimitMiddleware {
+    type Service = BodyLimit<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        BodyLimit {
+            service,
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit<S> {
+    service: S,
+    limit: Option<usize>,
+}
+
+impl<S, B> Service<Request<B>> for BodyLimit<S>
+where
+    S: Service<Request<B>, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future<'_> {
+        req.extensions_mut().insert(Limit(self.limit));
+        Box::pin(async move {
+            match self.service.call(req).await {
+                Ok(res) => Ok(res),
+                Err(e) => {
+                    let e = e.into();
+                    if crate::util::find_source::<DataTooLarge>(&*e).is_some() {
+                        Ok(DataTooLarge.into_response())
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// 保存在请求扩展中的请求体大小限制。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Limit(Option<usize>);
+
+impl Limit {
+    pub(crate) fn from_extensions(extensions: &Extensions) -> Option<usize> {
+        extensions
+            .get::<Self>()
+            .map_or(Some(DEFAULT_BODY_LIMIT), |limit| limit.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::body::{BoxBody, Bytes};
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{BoxError, Request};
+
+    use super::{body_limit, disable_body_limit, DEFAULT_BODY_LIMIT};
+    use crate::handler::handler;
+
+    async fn len(body: Bytes) -> String {
+        body.len().to_string()
+    }
+
+    fn request(len: usize) -> Request {
+        Request::new(BoxBody::new(vec![0; len]))
+    }
+
+    #[tokio::test]
+    async fn default_limit() {
+        let svc = handler(len);
+        let res = svc.call(request(DEFAULT_BODY_LIMIT)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = svc.call(request(DEFAULT_BODY_LIMIT + 1)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn custom_limit() {
+        let svc = body_limit(4).transform(handler(len));
+        let res = svc.call(request(4)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = svc.call(request(5)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let svc = disable_body_limit().transform(handler(len));
+        let res = svc.call(request(DEFAULT_BODY_LIMIT + 1)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // 中间件也会转换没有经过提取器的错误。
+        let svc = body_limit(4).transform(service_fn(|mut req: Request| async move {
+            let body = crate::extract::bytes(&mut req).await?;
+            Ok::<_, BoxError>(body.len().to_string().into_response())
+        }));
+        let res = svc.call(request(5)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
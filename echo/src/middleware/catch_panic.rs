@@ -0,0 +1,244 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+use futures_util::FutureExt;
+
+type ResponseFn = Arc<dyn Fn() -> Response + Send + Sync>;
+
+type PanicHook = Arc<dyn Fn(&(dyn Any + Send)) + Send + Sync>;
+
+/// 捕获内部服务的 panic，并返回 `500 Internal Server Error`。
+///
+/// 没有这个中间件时，处理器的 panic 会导致连接直接断开，客户端收不到任何响应。
+/// 中间件只能捕获调用服务和等待响应时的 panic，写入响应体时的 panic 仍然会断开连接。
+///
+/// 响应体默认为空，可以通过 [`body`](CatchPanicMiddleware::body) 设置；
+/// [`on_panic`](CatchPanicMiddleware::on_panic) 可以获取 panic 的参数用于记录日志。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::catch_panic;
+/// use echo::response::Html;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(
+///     catch_panic()
+///         .body(Html("<h1>Internal Server Error</h1>"))
+///         .on_panic(|payload| {
+///             let message = payload
+///                 .downcast_ref::<&str>()
+///                 .copied()
+///                 .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+///                 .unwrap_or("Box<dyn Any>");
+///             eprintln!("handler panicked: {message}");
+///         }),
+/// );
+/// ```
+#[inline]
+pub fn catch_panic() -> CatchPanicMiddleware {
+    CatchPanicMiddleware::new()
+}
+
+#[derive(Clone)]
+pub struct CatchPanicMiddleware {
+    response: ResponseFn,
+    on_panic: Option<PanicHook>,
+}
+
+impl CatchPanicMiddleware {
+    pub fn new() -> Self {
+        Self {
+            response: Arc::new(|| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+            on_panic: None,
+        }
+    }
+
+    /// 设置响应体，响应的状态码总是 `500 Internal Server Error`。
+    pub fn body<B>(mut self, body: B) -> Self
+    where
+        B: IntoResponse + Clone + Send + Sync + 'static,
+    {
+        self.response = Arc::new(move || {
+            let mut res = body.clone().into_response();
+            *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            res
+        });
+        self
+    }
+
+    /// 发生 panic 时调用，参数为 panic 的参数，通常是 `&'static str` 或者 `String`。
+    pub fn on_panic<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&(dyn Any + Send)) + Send + Sync + 'static,
+    {
+        self.on_panic = Some(Arc::new(f));
+        self
+    }
+}
+
+impl Default for CatchPanicMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for CatchPanicMiddleware {
+    type Service = CatchPanic<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        CatchPanic {
+            service,
+            response: self.response,
+            on_panic: self.on_panic,
+        }
+    }
+}
+
+impl fmt::Debug for CatchPanicMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanicMiddleware")
+            .field("on_panic", &self.on_panic.is_some())
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct CatchPanic<S> {
+    service: S,
+    response: ResponseFn,
+    on_panic: Option<PanicHook>,
+}
+
+impl<S> Service<Request> for CatchPanic<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let fut = std::panic::catch_unwind(AssertUnwindSafe(|| self.service.call(req)));
+
+        Box::pin(async move {
+            let payload = match fut {
+                Ok(fut) => match AssertUnwindSafe(fut).catch_unwind().await {
+                    Ok(res) => return res,
+                    Err(payload) => payload,
+                },
+                Err(payload) => payload,
+            };
+
+            if let Some(f) = &self.on_panic {
+                f(&*payload);
+            }
+            Ok((self.response)())
+        })
+    }
+}
+
+impl<S> fmt::Debug for CatchPanic<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("service", &self.service)
+            .field("on_panic", &self.on_panic.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::future::Ready;
+    use std::sync::{Arc, Mutex};
+
+    use echo_core::body::BodyExt;
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{Request, Response};
+
+    use super::catch_panic;
+    use crate::response::Html;
+
+    /// 记录 `on_panic` 收到的消息。
+    fn recorder() -> (Arc<Mutex<Vec<String>>>, super::CatchPanicMiddleware) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let middleware = catch_panic().on_panic({
+            let messages = messages.clone();
+            move |payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                messages.lock().unwrap().push(message);
+            }
+        });
+        (messages, middleware)
+    }
+
+    #[tokio::test]
+    async fn sync_panic() {
+        let (messages, middleware) = recorder();
+        let svc = middleware.transform(service_fn(
+            |_: Request| -> Ready<Result<Response, Infallible>> { panic!("sync") },
+        ));
+
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(*messages.lock().unwrap(), ["sync"]);
+    }
+
+    #[tokio::test]
+    async fn async_panic() {
+        let (messages, middleware) = recorder();
+        let svc = middleware.transform(service_fn(|req: Request| async move {
+            if req.uri().path() == "/panic" {
+                panic!("async {}", req.uri().path());
+            }
+            Ok::<_, Infallible>("ok".into_response())
+        }));
+
+        let req = Request::builder().uri("/panic").body(Default::default());
+        let res = svc.call(req.unwrap()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(*messages.lock().unwrap(), ["async /panic"]);
+
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(messages.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn body() {
+        let svc = catch_panic()
+            .body(Html("<h1>error</h1>"))
+            .transform(service_fn(
+                |_: Request| -> Ready<Result<Response, Infallible>> { panic!("boom") },
+            ));
+
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "<h1>error</h1>");
+    }
+}
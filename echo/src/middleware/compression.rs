@@ -0,0 +1,528 @@
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use async_compression::tokio::write::{BrotliEncoder, GzipEncoder, ZlibEncoder, ZstdEncoder};
+use async_compression::Level;
+use echo_core::body::{Body, BodyExt, BoxBody, Bytes, Frame};
+use echo_core::http::header::{self, HeaderMap, HeaderValue};
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+use tokio::io::AsyncWrite;
+
+use super::encoding::{Encoding, Encodings};
+use crate::extract::AcceptEncoding;
+
+/// 根据 `Accept-Encoding` 压缩响应体。
+///
+/// 支持 `gzip`、`deflate`、`br` 和 `zstd`，质量值相同时依次优先选择 `zstd`、`br`、`gzip`、
+/// `deflate`。响应体以流的方式逐帧压缩，每帧数据都会立即刷新，因此 SSE 等流式响应不会被缓冲。
+///
+/// 以下响应不会被压缩：
+///
+/// - 已经设置了 `Content-Encoding` 或 `Cache-Control: no-transform` 的响应；
+/// - 部分内容（`206 Partial Content` 或带有 `Content-Range`）的响应；
+/// - 图片、音视频、压缩包等本身已经压缩过的内容类型（SVG 除外）；
+/// - 已知大小小于 [`min_size`](CompressionMiddleware::min_size) 的响应体。
+///
+/// 可以被压缩的响应都会带上 `Vary: Accept-Encoding`。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::compression;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(compression().deflate(false).min_size(1024));
+/// ```
+#[inline]
+pub fn compression() -> CompressionMiddleware {
+    CompressionMiddleware::new()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionMiddleware {
+    encodings: Encodings,
+    min_size: u64,
+}
+
+impl CompressionMiddleware {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            encodings: Default::default(),
+            min_size: 32,
+        }
+    }
+
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.encodings.gzip = enable;
+        self
+    }
+
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.encodings.deflate = enable;
+        self
+    }
+
+    pub fn br(mut self, enable: bool) -> Self {
+        self.encodings.br = enable;
+        self
+    }
+
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.encodings.zstd = enable;
+        self
+    }
+
+    /// 设置需要压缩的响应体的最小大小（字节），默认为 32。
+    ///
+    /// 只对大小已知的响应体生效，流式响应总是会被压缩。
+    pub fn min_size(mut self, min: u64) -> Self {
+        self.min_size = min;
+        self
+    }
+}
+
+impl Default for CompressionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for CompressionMiddleware {
+    type Service = Compression<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Compression {
+            service,
+            encodings: self.encodings,
+            min_size: self.min_size,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Compression<S> {
+    service: S,
+    encodings: Encodings,
+    min_size: u64,
+}
+
+impl<S> Compression<S> {
+    fn negotiate(&self, req: &Request) -> Option<Encoding> {
+        let offers = self
+            .encodings
+            .iter()
+            .map(Encoding::as_str)
+            .collect::<Vec<_>>();
+        AcceptEncoding::from_headers(req.headers())
+            .negotiate(&offers)
+            .and_then(Encoding::from_name)
+    }
+
+    fn is_compressible(&self, res: &Response) -> bool {
+        let headers = res.headers();
+
+        if res.status() == StatusCode::PARTIAL_CONTENT
+            || headers.contains_key(header::CONTENT_RANGE)
+            || headers.contains_key(header::CONTENT_ENCODING)
+            || is_no_transform(headers)
+            || is_compressed_content_type(headers)
+        {
+            return false;
+        }
+
+        res.body()
+            .size_hint()
+            .exact()
+            .is_none_or(|size| size >= self.min_size)
+    }
+}
+
+impl<S> Service<Request> for Compression<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let encoding = self.negotiate(&req);
+
+        Box::pin(async move {
+            let mut res = self.service.call(req).await?;
+            if !self.is_compressible(&res) {
+                return Ok(res);
+            }
+
+            crate::util::add_vary(res.headers_mut(), "accept-encoding");
+
+            if let Some(encoding) = encoding {
+                let headers = res.headers_mut();
+                headers.insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(encoding.as_str()),
+                );
+                headers.remove(header::CONTENT_LENGTH);
+                headers.remove(header::ACCEPT_RANGES);
+
+                res = res.map(|body| CompressionBody::new(body, encoding).boxed());
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+impl<S> fmt::Debug for Compression<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compression")
+            .field("service", &self.service)
+            .field("encodings", &self.encodings)
+            .field("min_size", &self.min_size)
+            .finish()
+    }
+}
+
+fn is_no_transform(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+}
+
+fn is_compressed_content_type(headers: &HeaderMap) -> bool {
+    let Some(mime) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+    else {
+        return false;
+    };
+
+    match (mime.type_().as_str(), mime.subtype().as_str()) {
+        ("image", "svg") => false,
+        ("image" | "audio" | "video", _) => true,
+        ("font", subtype) => subtype.starts_with("woff"),
+        ("application", subtype) => matches!(
+            subtype,
+            "gzip"
+                | "x-gzip"
+                | "zip"
+                | "zstd"
+                | "x-bzip2"
+                | "x-xz"
+                | "x-7z-compressed"
+                | "x-rar-compressed"
+                | "vnd.rar"
+        ),
+        _ => false,
+    }
+}
+
+/// 压缩后的数据写入 `Vec<u8>`，因此编码器的写入操作总是立即完成。
+trait Encoder: AsyncWrite + Send + Unpin {
+    fn output(&mut self) -> &mut Vec<u8>;
+}
+
+macro_rules! impl_encoder {
+    ($($ty:ident),*) => {
+        $(
+            impl Encoder for $ty<Vec<u8>> {
+                fn output(&mut self) -> &mut Vec<u8> {
+                    self.get_mut()
+                }
+            }
+        )*
+    };
+}
+
+impl_encoder!(GzipEncoder, ZlibEncoder, BrotliEncoder, ZstdEncoder);
+
+struct CompressionBody {
+    body: BoxBody,
+    encoder: Box<dyn Encoder>,
+    trailers: Option<Frame<Bytes>>,
+    finished: bool,
+}
+
+impl CompressionBody {
+    fn new(body: BoxBody, encoding: Encoding) -> Self {
+        let encoder: Box<dyn Encoder> = match encoding {
+            Encoding::Gzip => Box::new(GzipEncoder::new(Vec::new())),
+            Encoding::Deflate => Box::new(ZlibEncoder::new(Vec::new())),
+            // 默认的最高压缩等级对动态内容来说太慢了。
+            Encoding::Brotli => {
+                Box::new(BrotliEncoder::with_quality(Vec::new(), Level::Precise(4)))
+            }
+            Encoding::Zstd => Box::new(ZstdEncoder::new(Vec::new())),
+        };
+        Self {
+            body,
+            encoder,
+            trailers: None,
+            finished: false,
+        }
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, mut data: &[u8]) -> Poll<io::Result<()>> {
+        while !data.is_empty() {
+            let n = ready!(Pin::new(&mut self.encoder).poll_write(cx, data))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            data = &data[n..];
+        }
+        Pin::new(&mut self.encoder).poll_flush(cx)
+    }
+
+    fn take_output(&mut self) -> Option<Frame<Bytes>> {
+        let output = std::mem::take(self.encoder.output());
+        (!output.is_empty()).then(|| Frame::data(output.into()))
+    }
+}
+
+impl Body for CompressionBody {
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if this.finished {
+                return Poll::Ready(this.trailers.take().map(Ok));
+            }
+
+            match ready!(Pin::new(&mut this.body).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => {
+                        ready!(this.poll_write(cx, &data))?;
+                    }
+                    Err(frame) => {
+                        this.trailers = Some(frame);
+                        ready!(Pin::new(&mut this.encoder).poll_shutdown(cx))?;
+                        this.finished = true;
+                    }
+                },
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    ready!(Pin::new(&mut this.encoder).poll_shutdown(cx))?;
+                    this.finished = true;
+                }
+            }
+
+            if let Some(frame) = this.take_output() {
+                return Poll::Ready(Some(Ok(frame)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder};
+    use echo_core::body::{BodyExt, BoxBody, Bytes, Frame, StreamBody};
+    use echo_core::http::header::{
+        ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE,
+        CONTENT_TYPE, VARY,
+    };
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{Request, Response};
+    use futures_util::{stream, FutureExt, StreamExt};
+    use tokio::io::{AsyncRead, AsyncReadExt};
+
+    use super::{compression, CompressionMiddleware};
+
+    fn text() -> String {
+        "hello, world! ".repeat(100)
+    }
+
+    async fn call(
+        middleware: CompressionMiddleware,
+        accept: &'static str,
+        body: String,
+    ) -> Response {
+        call_with(middleware, accept, move || body.clone().into_response()).await
+    }
+
+    async fn call_with<F>(middleware: CompressionMiddleware, accept: &'static str, f: F) -> Response
+    where
+        F: Fn() -> Response + Send + Sync + 'static,
+    {
+        let svc = middleware.transform(service_fn(move |_: Request| {
+            let res = f();
+            async move { Ok::<_, Infallible>(res) }
+        }));
+        let req = Request::builder()
+            .header(ACCEPT_ENCODING, accept)
+            .body(Default::default())
+            .unwrap();
+        svc.call(req).await.unwrap()
+    }
+
+    async fn read(mut reader: impl AsyncRead + Unpin) -> String {
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).await.unwrap();
+        buf
+    }
+
+    fn encoding(res: &Response) -> Option<&str> {
+        res.headers()
+            .get(CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let res = call(compression(), "gzip", text()).await;
+        assert_eq!(encoding(&res), Some("gzip"));
+        assert_eq!(res.headers()[VARY], "accept-encoding");
+        assert!(!res.headers().contains_key(CONTENT_LENGTH));
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.len() < text().len());
+        assert_eq!(read(GzipDecoder::new(&body[..])).await, text());
+
+        let res = call(compression(), "br", text()).await;
+        assert_eq!(encoding(&res), Some("br"));
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(read(BrotliDecoder::new(&body[..])).await, text());
+    }
+
+    #[tokio::test]
+    async fn negotiate() {
+        let cases = [
+            ("gzip;q=0.5, br;q=0.8", Some("br")),
+            ("br;q=0, gzip", Some("gzip")),
+            ("gzip, br, zstd", Some("zstd")),
+            ("deflate;q=0.1, *;q=0.5", Some("zstd")),
+            ("*;q=0, identity", None),
+            ("identity", None),
+        ];
+        for (accept, expected) in cases {
+            let res = call(compression(), accept, text()).await;
+            assert_eq!(encoding(&res), expected, "{accept}");
+        }
+
+        let res = call(compression().br(false), "br, gzip;q=0.5", text()).await;
+        assert_eq!(encoding(&res), Some("gzip"));
+    }
+
+    #[tokio::test]
+    async fn skip_small_body() {
+        let res = call(compression(), "gzip", "hello".to_owned()).await;
+        assert_eq!(encoding(&res), None);
+        assert!(!res.headers().contains_key(VARY));
+    }
+
+    #[tokio::test]
+    async fn flush_frames() {
+        let res = call_with(compression(), "gzip", || {
+            // 发送两个事件后保持连接，模拟 SSE。
+            let events = stream::iter(["data: a\n\n", "data: b\n\n"])
+                .map(|event| Ok::<_, Infallible>(Frame::data(Bytes::from(event))))
+                .chain(stream::pending());
+            let mut res = Response::new(BoxBody::new(StreamBody::new(events)));
+            res.headers_mut()
+                .insert(CONTENT_TYPE, "text/event-stream".parse().unwrap());
+            res
+        })
+        .await;
+        assert_eq!(encoding(&res), Some("gzip"));
+
+        // 每一帧都会立即输出压缩后的数据，不需要等待响应体结束。
+        let mut body = res.into_body();
+        let mut compressed = Vec::new();
+        for _ in 0..2 {
+            let frame = body.next().now_or_never().unwrap().unwrap().unwrap();
+            compressed.extend_from_slice(&frame.into_data().unwrap());
+        }
+        assert!(body.next().now_or_never().is_none());
+
+        let mut decoder = GzipDecoder::new(&compressed[..]);
+        let mut buf = [0; 64];
+        let mut decoded = Vec::new();
+        while let Ok(n @ 1..) = decoder.read(&mut buf).await {
+            decoded.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(decoded, b"data: a\n\ndata: b\n\n");
+    }
+
+    #[tokio::test]
+    async fn skip_partial_content() {
+        let res = call_with(compression(), "gzip", || {
+            let mut res = text().into_response();
+            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+            res
+        })
+        .await;
+        assert_eq!(encoding(&res), None);
+
+        let res = call_with(compression(), "gzip", || {
+            let mut res = text().into_response();
+            res.headers_mut()
+                .insert(CONTENT_RANGE, "bytes 0-1399/2000".parse().unwrap());
+            res
+        })
+        .await;
+        assert_eq!(encoding(&res), None);
+        assert!(!res.headers().contains_key(VARY));
+    }
+
+    #[tokio::test]
+    async fn skip_compressed_content_type() {
+        let cases = [
+            ("image/png", None),
+            ("video/mp4", None),
+            ("font/woff2", None),
+            ("application/zip", None),
+            ("application/gzip", None),
+            ("image/svg+xml", Some("gzip")),
+            ("application/json", Some("gzip")),
+        ];
+        for (content_type, expected) in cases {
+            let res = call_with(compression(), "gzip", || {
+                let mut res = text().into_response();
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, content_type.parse().unwrap());
+                res
+            })
+            .await;
+            assert_eq!(encoding(&res), expected, "{content_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn skip_no_transform() {
+        let res = call_with(compression(), "gzip", || {
+            let mut res = text().into_response();
+            res.headers_mut()
+                .insert(CACHE_CONTROL, "public, No-Transform".parse().unwrap());
+            res
+        })
+        .await;
+        assert_eq!(encoding(&res), None);
+        assert!(!res.headers().contains_key(VARY));
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// 限制同时处理的请求数。
+///
+/// 达到限制时新的请求默认排队等待，调用 [`load_shed`](ConcurrencyLimitMiddleware::load_shed)
+/// 后则直接返回 `503 Service Unavailable`。请求在内部服务返回响应后即不再计数，不包括写入响应体的时间。
+///
+/// 中间件及其克隆共享同一个限制：应用到 [`Router`](crate::route::Router) 上是全局的限制，
+/// 为每个路由分别创建中间件则是按路由的限制。
+///
+/// 还可以通过 [`adaptive`](ConcurrencyLimitMiddleware::adaptive) 根据响应耗时自动调整限制，
+/// 参见 [`AdaptiveLimit`]。
+///
+/// # 例子
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use echo::handler::handler;
+/// use echo::middleware::{concurrency_limit, AdaptiveLimit};
+/// use echo::route::{get, post, Router};
+/// use echo::service::ServiceExt;
+///
+/// async fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// async fn upload() -> &'static str {
+///     "ok"
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler(index)))
+///     .route("/upload", post(handler(upload)).with(concurrency_limit(4)))
+///     .with(
+///         concurrency_limit(512)
+///             .load_shed()
+///             .adaptive(AdaptiveLimit::new(Duration::from_millis(200))),
+///     );
+/// ```
+#[inline]
+pub fn concurrency_limit(limit: usize) -> ConcurrencyLimitMiddleware {
+    ConcurrencyLimitMiddleware::new(limit)
+}
+
+/// 根据响应耗时调整并发限制。
+///
+/// 采用加性增、乘性减的策略：耗时不超过目标时，每完成与当前限制相同数量的请求限制加一，
+/// 直到 [`concurrency_limit`] 指定的上限；耗时超过目标时限制乘以
+/// [`backoff`](AdaptiveLimit::backoff)，但不低于 [`min_limit`](AdaptiveLimit::min_limit)。
+/// 每完成与当前限制相同数量的请求最多缩小一次，同一批慢请求只会使限制缩小一次。
+/// 限制从上限开始。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveLimit {
+    target: Duration,
+    min_limit: usize,
+    backoff: f64,
+}
+
+impl AdaptiveLimit {
+    pub fn new(target: Duration) -> Self {
+        Self {
+            target,
+            min_limit: 1,
+            backoff: 0.9,
+        }
+    }
+
+    /// 设置限制的下限，默认为 1。
+    pub fn min_limit(mut self, limit: usize) -> Self {
+        self.min_limit = limit.max(1);
+        self
+    }
+
+    /// 设置耗时超过目标时限制缩小的比例，默认为 0.9。
+    ///
+    /// # Panics
+    ///
+    /// `backoff` 不在 `(0, 1)` 之间时 panic。
+    pub fn backoff(mut self, backoff: f64) -> Self {
+        assert!(
+            backoff > 0.0 && backoff < 1.0,
+            "backoff must be between 0 and 1"
+        );
+        self.backoff = backoff;
+        self
+    }
+}
+
+struct Limiter {
+    semaphore: Semaphore,
+    max: usize,
+    adaptive: Option<(AdaptiveLimit, Mutex<AdaptiveState>)>,
+}
+
+#[derive(Debug)]
+struct AdaptiveState {
+    limit: usize,
+    successes: usize,
+    /// 上次缩小限制之后完成的请求数。
+    completed: usize,
+    /// 缩小限制后还需要收回的许可数。
+    pending: usize,
+}
+
+impl Limiter {
+    fn new(max: usize, adaptive: Option<AdaptiveLimit>) -> Self {
+        Self {
+            semaphore: Semaphore::new(max),
+            max,
+            adaptive: adaptive.map(|config| {
+                let state = AdaptiveState {
+                    limit: max,
+                    successes: 0,
+                    completed: max,
+                    pending: 0,
+                };
+                (config, Mutex::new(state))
+            }),
+        }
+    }
+
+    fn limit(&self) -> usize {
+        match &self.adaptive {
+            Some((_, state)) => state.lock().unwrap().limit,
+            None => self.max,
+        }
+    }
+
+    fn release(&self, permit: SemaphorePermit<'_>, latency: Duration) {
+        let Some((config, state)) = &self.adaptive else {
+            return;
+        };
+        let mut state = state.lock().unwrap();
+        state.completed = state.completed.saturating_add(1);
+
+        if latency > config.target {
+            // 缩小之前发出的请求仍然可能很慢，一个窗口内只缩小一次。
+            if state.completed >= state.limit {
+                let limit = ((state.limit as f64 * config.backoff) as usize)
+                    .max(config.min_limit)
+                    .min(state.limit);
+                state.pending += state.limit - limit;
+                state.limit = limit;
+                state.completed = 0;
+            }
+            state.successes = 0;
+        } else if state.limit < self.max {
+            state.successes += 1;
+            if state.successes >= state.limit {
+                state.limit += 1;
+                state.successes = 0;
+                if state.pending > 0 {
+                    state.pending -= 1;
+                } else {
+                    self.semaphore.add_permits(1);
+                }
+            }
+        }
+
+        if state.pending > 0 {
+            state.pending -= 1;
+            permit.forget();
+        }
+    }
+}
+
+impl fmt::Debug for Limiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Limiter")
+            .field("limit", &self.limit())
+            .field("available", &self.semaphore.available_permits())
+            .field(
+                "adaptive",
+                &self.adaptive.as_ref().map(|(config, _)| config),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitMiddleware {
+    limiter: Arc<Limiter>,
+    load_shed: bool,
+}
+
+impl ConcurrencyLimitMiddleware {
+    /// # Panics
+    ///
+    /// `limit` 为 0 时 panic。
+    pub fn new(limit: usize) -> Self {
+        assert!(limit > 0, "concurrency limit must be greater than 0");
+        Self {
+            limiter: Arc::new(Limiter::new(limit, None)),
+            load_shed: false,
+        }
+    }
+
+    /// 达到限制时直接返回 `503 Service Unavailable`，而不是排队等待。
+    pub fn load_shed(mut self) -> Self {
+        self.load_shed = true;
+        self
+    }
+
+    /// 根据响应耗时调整限制，[`concurrency_limit`] 指定的值作为上限。
+    ///
+    /// 会创建新的限制，应当在克隆中间件之前调用。
+    pub fn adaptive(mut self, config: AdaptiveLimit) -> Self {
+        self.limiter = Arc::new(Limiter::new(self.limiter.max, Some(config)));
+        self
+    }
+
+    /// 当前的限制。
+    pub fn limit(&self) -> usize {
+        self.limiter.limit()
+    }
+}
+
+impl<S> Middleware<S> for ConcurrencyLimitMiddleware {
+    type Service = ConcurrencyLimit<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        ConcurrencyLimit {
+            service,
+            limiter: self.limiter,
+            load_shed: self.load_shed,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit<S> {
+    service: S,
+    limiter: Arc<Limiter>,
+    load_shed: bool,
+}
+
+impl<S> Service<Request> for ConcurrencyLimit<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        Box::pin(async move {
+            let semaphore = &self.limiter.semaphore;
+            let permit = if self.load_shed {
+                match semaphore.try_acquire() {
+                    Ok(permit) => permit,
+                    Err(_) => return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response()),
+                }
+            } else {
+                // 信号量不会被关闭。
+                semaphore.acquire().await.unwrap()
+            };
+
+            let start = Instant::now();
+            let res = self.service.call(req).await;
+            self.limiter.release(permit, start.elapsed());
+            res
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{Request, Response};
+    use futures_util::poll;
+    use tokio::sync::Semaphore;
+
+    use super::{concurrency_limit, AdaptiveLimit, ConcurrencyLimitMiddleware, Limiter};
+
+    /// 每个请求等待 `gate` 的一个许可后返回，`calls` 记录进入内部服务的请求数。
+    fn service(
+        middleware: ConcurrencyLimitMiddleware,
+        gate: Arc<Semaphore>,
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<Request, Response = Response, Error = Infallible> {
+        middleware.transform(service_fn(move |_: Request| {
+            let gate = gate.clone();
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                gate.acquire().await.unwrap().forget();
+                Ok("done".into_response())
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn queue() {
+        let gate = Arc::new(Semaphore::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = service(concurrency_limit(1), gate.clone(), calls.clone());
+
+        let first = svc.call(Request::default());
+        let second = svc.call(Request::default());
+        tokio::pin!(first, second);
+        assert!(poll!(first.as_mut()).is_pending());
+        assert!(poll!(second.as_mut()).is_pending());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        gate.add_permits(1);
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+        assert!(poll!(second.as_mut()).is_pending());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        gate.add_permits(1);
+        assert_eq!(second.await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn load_shed() {
+        let gate = Arc::new(Semaphore::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = service(concurrency_limit(1).load_shed(), gate.clone(), calls.clone());
+
+        let first = svc.call(Request::default());
+        tokio::pin!(first);
+        assert!(poll!(first.as_mut()).is_pending());
+
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        gate.add_permits(2);
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    const TARGET: Duration = Duration::from_millis(100);
+    const FAST: Duration = Duration::from_millis(10);
+    const SLOW: Duration = Duration::from_millis(500);
+
+    /// 依次以 `latency` 完成 `n` 个请求。
+    fn complete(limiter: &Limiter, n: usize, latency: Duration) {
+        for _ in 0..n {
+            let permit = limiter.semaphore.try_acquire().unwrap();
+            limiter.release(permit, latency);
+        }
+    }
+
+    #[test]
+    fn adaptive_decrease() {
+        let limiter = Limiter::new(20, Some(AdaptiveLimit::new(TARGET).min_limit(5)));
+
+        // 同一批慢请求只缩小一次。
+        complete(&limiter, 10, SLOW);
+        assert_eq!(limiter.limit(), 18);
+        assert_eq!(limiter.semaphore.available_permits(), 18);
+
+        complete(&limiter, 8, SLOW);
+        assert_eq!(limiter.limit(), 18);
+        complete(&limiter, 1, SLOW);
+        assert_eq!(limiter.limit(), 16);
+
+        // 持续变慢时逐个窗口缩小，直到下限。
+        complete(&limiter, 1000, SLOW);
+        assert_eq!(limiter.limit(), 5);
+        assert_eq!(limiter.semaphore.available_permits(), 5);
+    }
+
+    #[test]
+    fn adaptive_increase() {
+        let limiter = Limiter::new(10, Some(AdaptiveLimit::new(TARGET).backoff(0.5)));
+        complete(&limiter, 1, SLOW);
+        assert_eq!(limiter.limit(), 5);
+
+        // 每完成与限制相同数量的请求加一。
+        complete(&limiter, 4, FAST);
+        assert_eq!(limiter.limit(), 5);
+        complete(&limiter, 1, FAST);
+        assert_eq!(limiter.limit(), 6);
+        assert_eq!(limiter.semaphore.available_permits(), 6);
+
+        // 不超过上限。
+        complete(&limiter, 1000, FAST);
+        assert_eq!(limiter.limit(), 10);
+        assert_eq!(limiter.semaphore.available_permits(), 10);
+    }
+}
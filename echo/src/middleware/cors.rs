@@ -0,0 +1,618 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use echo_core::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use echo_core::http::{Method, StatusCode};
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+use regex::Regex;
+
+use crate::util::add_vary;
+
+/// 处理跨域资源共享（CORS）。
+///
+/// 预检请求（带有 `Origin` 和 `Access-Control-Request-Method` 的 `OPTIONS` 请求）由中间件直接返回
+/// `204 No Content`，不会进入内部服务，因此无需为每个路由注册 `OPTIONS` 方法。来源不被允许时，
+/// 响应中不包含任何 `Access-Control-*` 头，由浏览器拒绝请求。
+///
+/// 默认允许任意来源，允许 `GET`、`HEAD`、`PUT`、`PATCH`、`POST`、`DELETE` 方法，并允许预检请求中
+/// 声明的所有请求头。`Access-Control-Allow-Origin` 与请求有关时，响应会带上 `Vary: Origin`。
+///
+/// 内部服务返回的错误不会带有 CORS 头，如果需要浏览器读取错误响应，
+/// 应当在将错误转换为响应之后再应用该中间件。
+///
+/// # Panics
+///
+/// [`allow_credentials`](CorsMiddleware::allow_credentials) 与 [`AllowOrigin::any`]
+/// 同时使用时，应用中间件会 panic。
+///
+/// # 例子
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use echo::http::{header, HeaderValue, Method};
+/// use echo::middleware::{cors, AllowOrigin};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(
+///     cors()
+///         .allow_origin(AllowOrigin::list([
+///             HeaderValue::from_static("https://example.com"),
+///             HeaderValue::from_static("https://admin.example.com"),
+///         ]))
+///         .allow_methods([Method::GET, Method::POST])
+///         .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+///         .expose_headers([header::ETAG])
+///         .allow_credentials(true)
+///         .max_age(Duration::from_secs(3600)),
+/// );
+/// ```
+#[inline]
+pub fn cors() -> CorsMiddleware {
+    CorsMiddleware::new()
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsMiddleware {
+    allow_origin: AllowOrigin,
+    allow_methods: Allow<Vec<Method>>,
+    allow_headers: Allow<Vec<HeaderName>>,
+    expose_headers: Vec<HeaderName>,
+    allow_credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl CorsMiddleware {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            allow_origin: AllowOrigin::any(),
+            allow_methods: Allow::List(vec![
+                Method::GET,
+                Method::HEAD,
+                Method::PUT,
+                Method::PATCH,
+                Method::POST,
+                Method::DELETE,
+            ]),
+            allow_headers: Allow::Mirror,
+            expose_headers: Vec::new(),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// 设置允许的来源，默认允许任意来源。
+    pub fn allow_origin(mut self, origin: AllowOrigin) -> Self {
+        self.allow_origin = origin;
+        self
+    }
+
+    /// 设置预检请求允许的方法。
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.allow_methods = Allow::List(methods.into_iter().collect());
+        self
+    }
+
+    /// 允许预检请求中声明的任意方法。
+    pub fn allow_any_method(mut self) -> Self {
+        self.allow_methods = Allow::Mirror;
+        self
+    }
+
+    /// 设置预检请求允许的请求头。
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.allow_headers = Allow::List(headers.into_iter().collect());
+        self
+    }
+
+    /// 允许预检请求中声明的任意请求头，这是默认行为。
+    pub fn allow_any_header(mut self) -> Self {
+        self.allow_headers = Allow::Mirror;
+        self
+    }
+
+    /// 设置允许浏览器读取的响应头。
+    pub fn expose_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.expose_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// 是否允许携带 Cookie 等凭据，默认为 `false`。
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// 设置预检请求结果的缓存时间。
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+impl Default for CorsMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for CorsMiddleware {
+    type Service = Cors<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        assert!(
+            !(self.allow_credentials && matches!(self.allow_origin.0, Origin::Any)),
+            "CORS: `allow_credentials(true)` can not be used with `AllowOrigin::any()`"
+        );
+
+        let config = Config {
+            allow_methods: match self.allow_methods {
+                Allow::List(methods) => Allow::List(join(methods.iter().map(Method::as_str))),
+                Allow::Mirror => Allow::Mirror,
+            },
+            allow_headers: match self.allow_headers {
+                Allow::List(headers) => Allow::List(join(headers.iter().map(HeaderName::as_str))),
+                Allow::Mirror => Allow::Mirror,
+            },
+            expose_headers: (!self.expose_headers.is_empty())
+                .then(|| join(self.expose_headers.iter().map(HeaderName::as_str))),
+            max_age: self.max_age.map(|max_age| max_age.as_secs().into()),
+            allow_credentials: self.allow_credentials,
+            allow_origin: self.allow_origin,
+        };
+
+        Cors {
+            service,
+            config: Arc::new(config),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cors<S> {
+    service: S,
+    config: Arc<Config>,
+}
+
+#[derive(Debug)]
+struct Config {
+    allow_origin: AllowOrigin,
+    allow_methods: Allow<HeaderValue>,
+    allow_headers: Allow<HeaderValue>,
+    expose_headers: Option<HeaderValue>,
+    allow_credentials: bool,
+    max_age: Option<HeaderValue>,
+}
+
+impl Config {
+    fn is_preflight(req: &Request) -> bool {
+        req.method() == Method::OPTIONS
+            && req.headers().contains_key(header::ORIGIN)
+            && req
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    /// 添加 `Access-Control-Allow-Origin` 等预检请求和实际请求共有的响应头，
+    /// 来源不被允许时返回 `false`。
+    fn apply_origin(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) -> bool {
+        if !matches!(self.allow_origin.0, Origin::Any) {
+            add_vary(headers, "origin");
+        }
+
+        let Some(value) = origin.and_then(|origin| self.allow_origin.to_header(origin)) else {
+            return false;
+        };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        true
+    }
+
+    fn preflight(&self, req: &Request) -> Response {
+        let mut res = Response::default();
+        *res.status_mut() = StatusCode::NO_CONTENT;
+
+        let headers = res.headers_mut();
+        let allowed = self.apply_origin(req.headers().get(header::ORIGIN), headers);
+        add_vary(headers, "access-control-request-method");
+        add_vary(headers, "access-control-request-headers");
+        if !allowed {
+            return res;
+        }
+
+        let requested_method = req.headers().get(header::ACCESS_CONTROL_REQUEST_METHOD);
+        let allow_methods = match &self.allow_methods {
+            Allow::List(value) => Some(value),
+            Allow::Mirror => requested_method,
+        };
+        if let Some(value) = allow_methods {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value.clone());
+        }
+
+        let requested_headers = req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS);
+        let allow_headers = match &self.allow_headers {
+            Allow::List(value) => Some(value),
+            Allow::Mirror => requested_headers,
+        };
+        if let Some(value) = allow_headers {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value.clone());
+        }
+
+        if let Some(value) = &self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, value.clone());
+        }
+
+        res
+    }
+}
+
+impl<S> Service<Request> for Cors<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let config = &self.config;
+        if Config::is_preflight(&req) {
+            let res = config.preflight(&req);
+            return Box::pin(async move { Ok(res) });
+        }
+
+        let origin = req.headers().get(header::ORIGIN).cloned();
+
+        Box::pin(async move {
+            let mut res = self.service.call(req).await?;
+
+            let headers = res.headers_mut();
+            if config.apply_origin(origin.as_ref(), headers) {
+                if let Some(value) = &config.expose_headers {
+                    headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value.clone());
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// 允许的来源。
+///
+/// 除 [`any`](AllowOrigin::any) 外，`Access-Control-Allow-Origin` 的值为请求的 `Origin`。
+#[derive(Clone)]
+pub struct AllowOrigin(Origin);
+
+#[derive(Clone)]
+enum Origin {
+    Any,
+    Exact(HeaderValue),
+    List(Vec<HeaderValue>),
+    Regex(Regex),
+    Predicate(Arc<dyn Fn(&HeaderValue) -> bool + Send + Sync>),
+    Mirror,
+}
+
+impl AllowOrigin {
+    /// 允许任意来源，响应 `Access-Control-Allow-Origin: *`。
+    pub fn any() -> Self {
+        Self(Origin::Any)
+    }
+
+    /// 只允许一个来源。
+    pub fn exact(origin: HeaderValue) -> Self {
+        Self(Origin::Exact(origin))
+    }
+
+    /// 允许列表中的来源。
+    pub fn list<I>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderValue>,
+    {
+        Self(Origin::List(origins.into_iter().collect()))
+    }
+
+    /// 允许与正则表达式匹配的来源。
+    ///
+    /// 匹配不是完整匹配，通常需要使用 `^` 和 `$`，例如 `^https://([a-z0-9-]+\.)?example\.com$`。
+    pub fn regex(regex: Regex) -> Self {
+        Self(Origin::Regex(regex))
+    }
+
+    /// 允许 `f` 返回 `true` 的来源。
+    pub fn predicate<F>(f: F) -> Self
+    where
+        F: Fn(&HeaderValue) -> bool + Send + Sync + 'static,
+    {
+        Self(Origin::Predicate(Arc::new(f)))
+    }
+
+    /// 允许任意来源，并返回请求的 `Origin`，可以与凭据一起使用。
+    pub fn mirror_request() -> Self {
+        Self(Origin::Mirror)
+    }
+
+    fn to_header(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        let allowed = match &self.0 {
+            Origin::Any => return Some(HeaderValue::from_static("*")),
+            Origin::Exact(exact) => exact == origin,
+            Origin::List(list) => list.contains(origin),
+            Origin::Regex(regex) => origin.to_str().is_ok_and(|s| regex.is_match(s)),
+            Origin::Predicate(f) => f(origin),
+            Origin::Mirror => true,
+        };
+        allowed.then(|| origin.clone())
+    }
+}
+
+impl fmt::Debug for AllowOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Origin::Any => f.write_str("Any"),
+            Origin::Exact(origin) => f.debug_tuple("Exact").field(origin).finish(),
+            Origin::List(list) => f.debug_tuple("List").field(list).finish(),
+            Origin::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            Origin::Predicate(_) => f.write_str("Predicate"),
+            Origin::Mirror => f.write_str("Mirror"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Allow<T> {
+    List(T),
+    Mirror,
+}
+
+fn join<'a>(items: impl Iterator<Item = &'a str>) -> HeaderValue {
+    let value = items.collect::<Vec<_>>().join(", ");
+    HeaderValue::try_from(value).expect("method and header names are valid header values")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use echo_core::http::header::{self, HeaderName, HeaderValue};
+    use echo_core::http::{Method, StatusCode};
+    use echo_core::middleware::Middleware;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{Request, Response};
+    use regex::Regex;
+
+    use super::{cors, AllowOrigin, CorsMiddleware};
+
+    const ALLOWED: &str = "https://example.com";
+    const DENIED: &str = "https://evil.com";
+
+    async fn call(middleware: CorsMiddleware, req: Request) -> (Response, usize) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = middleware.transform(service_fn({
+            let calls = calls.clone();
+            move |_: Request| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                async { Ok::<Response, Infallible>(Response::default()) }
+            }
+        }));
+        let res = svc.call(req).await.unwrap();
+        (res, calls.load(Ordering::Relaxed))
+    }
+
+    fn request(method: Method, headers: &[(HeaderName, &'static str)]) -> Request {
+        let mut req = Request::default();
+        *req.method_mut() = method;
+        for (name, value) in headers {
+            req.headers_mut()
+                .insert(name, HeaderValue::from_static(value));
+        }
+        req
+    }
+
+    fn preflight(origin: &'static str) -> Request {
+        request(
+            Method::OPTIONS,
+            &[
+                (header::ORIGIN, origin),
+                (header::ACCESS_CONTROL_REQUEST_METHOD, "PUT"),
+                (
+                    header::ACCESS_CONTROL_REQUEST_HEADERS,
+                    "x-custom, content-type",
+                ),
+            ],
+        )
+    }
+
+    fn actual(origin: &'static str) -> Request {
+        request(Method::GET, &[(header::ORIGIN, origin)])
+    }
+
+    fn vary(res: &Response) -> Vec<&str> {
+        res.headers()
+            .get_all(header::VARY)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    fn allowed() -> AllowOrigin {
+        AllowOrigin::list([HeaderValue::from_static(ALLOWED)])
+    }
+
+    #[tokio::test]
+    async fn preflight_request() {
+        let (res, calls) = call(cors(), preflight(ALLOWED)).await;
+        assert_eq!(calls, 0);
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let headers = res.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_METHODS],
+            "GET, HEAD, PUT, PATCH, POST, DELETE"
+        );
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "x-custom, content-type"
+        );
+        assert!(!headers.contains_key(header::ACCESS_CONTROL_MAX_AGE));
+        assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+        let middleware = cors()
+            .allow_origin(allowed())
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+            .allow_credentials(true)
+            .max_age(std::time::Duration::from_secs(600));
+        let (res, _) = call(middleware, preflight(ALLOWED)).await;
+        let headers = res.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], ALLOWED);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, POST");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type, authorization"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+        let (res, _) = call(cors().allow_any_method(), preflight(ALLOWED)).await;
+        assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_METHODS], "PUT");
+
+        // 没有 `Access-Control-Request-Method` 的 `OPTIONS` 请求不是预检请求。
+        let req = request(Method::OPTIONS, &[(header::ORIGIN, ALLOWED)]);
+        let (res, calls) = call(cors(), req).await;
+        assert_eq!(calls, 1);
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn disallowed_origin() {
+        let middlewares = [
+            cors().allow_origin(allowed()).allow_credentials(true),
+            cors().allow_origin(AllowOrigin::exact(HeaderValue::from_static(ALLOWED))),
+        ];
+        for middleware in middlewares {
+            let (res, calls) = call(middleware.clone(), preflight(DENIED)).await;
+            assert_eq!(calls, 0);
+            assert_eq!(res.status(), StatusCode::NO_CONTENT);
+            for name in [
+                header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+            ] {
+                assert!(!res.headers().contains_key(&name), "{name}");
+            }
+
+            let (res, calls) = call(middleware, actual(DENIED)).await;
+            assert_eq!(calls, 1);
+            assert!(!res
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+            assert!(!res
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+        }
+    }
+
+    #[tokio::test]
+    async fn vary_origin() {
+        let origins = [
+            allowed(),
+            AllowOrigin::exact(HeaderValue::from_static(ALLOWED)),
+            AllowOrigin::regex(Regex::new(r"^https://([a-z0-9-]+\.)?example\.com$").unwrap()),
+            AllowOrigin::predicate(|origin| origin.as_bytes().ends_with(b"example.com")),
+            AllowOrigin::mirror_request(),
+        ];
+        for origin in origins {
+            let middleware = cors().allow_origin(origin.clone());
+            let (res, _) = call(middleware.clone(), actual(ALLOWED)).await;
+            assert_eq!(
+                res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+                ALLOWED,
+                "{origin:?}"
+            );
+            assert_eq!(vary(&res), ["origin"], "{origin:?}");
+
+            // 来源不被允许时也需要 `Vary`，以免缓存的响应被用于其它来源。
+            let (res, _) = call(middleware.clone(), actual(DENIED)).await;
+            let mirror = matches!(origin.0, super::Origin::Mirror);
+            assert_eq!(
+                res.headers()
+                    .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+                mirror,
+                "{origin:?}"
+            );
+            assert_eq!(vary(&res), ["origin"], "{origin:?}");
+
+            let (res, _) = call(middleware, preflight(ALLOWED)).await;
+            assert_eq!(
+                vary(&res),
+                [
+                    "origin",
+                    "access-control-request-method",
+                    "access-control-request-headers"
+                ]
+            );
+        }
+
+        let (res, _) = call(cors(), actual(ALLOWED)).await;
+        assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(vary(&res).is_empty());
+    }
+
+    #[tokio::test]
+    async fn expose_headers() {
+        let middleware = cors()
+            .allow_origin(allowed())
+            .expose_headers([header::ETAG, HeaderName::from_static("x-request-id")]);
+        let (res, _) = call(middleware.clone(), actual(ALLOWED)).await;
+        assert_eq!(
+            res.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS],
+            "etag, x-request-id"
+        );
+
+        let (res, _) = call(middleware.clone(), actual(DENIED)).await;
+        assert!(!res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_EXPOSE_HEADERS));
+        let (res, _) = call(middleware, preflight(ALLOWED)).await;
+        assert!(!res
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_EXPOSE_HEADERS));
+    }
+
+    #[test]
+    #[should_panic(expected = "can not be used with `AllowOrigin::any()`")]
+    fn credentials_with_any_origin() {
+        let _ = cors()
+            .allow_credentials(true)
+            .transform(service_fn(|_: Request| async {
+                Ok::<Response, Infallible>(Response::default())
+            }));
+    }
+}
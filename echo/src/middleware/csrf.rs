@@ -0,0 +1,641 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use echo_core::body::BoxBody;
+use echo_core::http::header::{self, HeaderName, HeaderValue};
+use echo_core::http::request::Parts;
+use echo_core::http::{Method, StatusCode};
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+use crate::cookie::{Cookie, CookieJar, SameSite};
+use crate::extract::{has_content_type, ExtractExtensionError, FromRequestParts};
+use crate::util::{random_token, set_cookie};
+
+/// 默认的 CSRF 令牌请求头。
+pub const X_CSRF_TOKEN: HeaderName = HeaderName::from_static("x-csrf-token");
+
+const SEC_FETCH_SITE: HeaderName = HeaderName::from_static("sec-fetch-site");
+
+/// 防御跨站请求伪造（CSRF）。
+///
+/// 中间件为每个客户端分配一个令牌，以 [`CsrfToken`] 的形式保存在请求扩展中，处理器可以将其渲染到表单中。
+/// 对于 `GET`、`HEAD`、`OPTIONS` 和 `TRACE` 以外的请求，中间件会依次检查：
+///
+/// 1. `Sec-Fetch-Site` 和 `Origin` 头，浏览器发送这些头时请求必须来自同源或者
+///    [`trusted_origins`](CsrfMiddleware::trusted_origins) 中的源。比较 `Origin` 时站点的协议由
+///    [`secure`](CsrfMiddleware::secure) 决定，默认为 `https`；
+/// 2. 请求头 `x-csrf-token` 或者 `application/x-www-form-urlencoded` 表单字段 `_csrf`
+///    中的令牌必须与分配的令牌一致。读取表单时受 [`body_limit`](super::body_limit) 的限制，
+///    `multipart/form-data` 表单需要通过请求头提交令牌。
+///
+/// 检查失败时返回 `403 Forbidden`，参见 [`CsrfError`]。
+///
+/// 默认使用双重提交 Cookie：令牌保存在名为 `__Host-csrf_token` 的 Cookie 中（`secure(false)` 时为
+/// `csrf_token`）。令牌没有与用户绑定，能够为本站设置 Cookie 的子域名可以用自己的令牌覆盖它；
+/// `__Host-` 前缀禁止子域名设置该 Cookie，因此不应当去掉该前缀。启用 `session` 特性时，可以通过
+/// [`session`](CsrfMiddleware::session) 将令牌保存在会话中，此时需要在外层应用 [`session`](super::session) 中间件。
+///
+/// # 例子
+///
+/// ```
+/// use echo::handler::handler;
+/// use echo::middleware::{csrf, CsrfToken};
+/// use echo::response::Html;
+/// use echo::route::{get, post, Router};
+/// use echo::service::ServiceExt;
+///
+/// async fn show(token: CsrfToken) -> Html<String> {
+///     Html(format!(
+///         r#"<form method="post">
+///             <input type="hidden" name="_csrf" value="{token}">
+///             <input name="title">
+///         </form>"#
+///     ))
+/// }
+///
+/// async fn submit() -> &'static str {
+///     "ok"
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler(show)))
+///     .route("/", post(handler(submit)))
+///     .with(csrf());
+/// ```
+#[inline]
+pub fn csrf() -> CsrfMiddleware {
+    CsrfMiddleware::new()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    Cookie,
+    #[cfg(feature = "session")]
+    Session,
+}
+
+#[derive(Debug, Clone)]
+struct CsrfConfig {
+    storage: Storage,
+    cookie_name: Option<Cow<'static, str>>,
+    secure: bool,
+    header: HeaderName,
+    field: Cow<'static, str>,
+    trusted_origins: Vec<HeaderValue>,
+}
+
+impl CsrfConfig {
+    fn cookie_name(&self) -> Cow<'static, str> {
+        match &self.cookie_name {
+            Some(name) => name.clone(),
+            None if self.secure => Cow::Borrowed("__Host-csrf_token"),
+            None => Cow::Borrowed("csrf_token"),
+        }
+    }
+
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        Cookie::build((self.cookie_name(), value))
+            .path("/")
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(SameSite::Lax)
+            .build()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsrfMiddleware {
+    config: CsrfConfig,
+}
+
+impl CsrfMiddleware {
+    pub fn new() -> Self {
+        Self {
+            config: CsrfConfig {
+                storage: Storage::Cookie,
+                cookie_name: None,
+                secure: true,
+                header: X_CSRF_TOKEN,
+                field: Cow::Borrowed("_csrf"),
+                trusted_origins: Vec::new(),
+            },
+        }
+    }
+
+    /// 设置保存令牌的 Cookie 的名称，默认为 `__Host-csrf_token`，`secure(false)` 时为 `csrf_token`。
+    pub fn cookie_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.config.cookie_name = Some(name.into());
+        self
+    }
+
+    /// 设置 Cookie 的 `Secure` 属性，默认为 `true`，同时表示站点是否使用 HTTPS。
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.config.secure = secure;
+        self
+    }
+
+    /// 设置提交令牌的请求头，默认为 `x-csrf-token`。
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.config.header = header;
+        self
+    }
+
+    /// 设置提交令牌的表单字段，默认为 `_csrf`。
+    pub fn field(mut self, field: impl Into<Cow<'static, str>>) -> Self {
+        self.config.field = field.into();
+        self
+    }
+
+    /// 允许来自这些源的跨源请求，例如 `https://app.example.com`。
+    pub fn trusted_origins<I>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderValue>,
+    {
+        self.config.trusted_origins.extend(origins);
+        self
+    }
+
+    /// 将令牌保存在会话中，而不是单独的 Cookie 中。
+    #[cfg(feature = "session")]
+    pub fn session(mut self) -> Self {
+        self.config.storage = Storage::Session;
+        self
+    }
+}
+
+impl Default for CsrfMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for CsrfMiddleware {
+    type Service = Csrf<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Csrf {
+            service,
+            config: Arc::new(self.config),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Csrf<S> {
+    service: S,
+    config: Arc<CsrfConfig>,
+}
+
+impl<S> Csrf<S> {
+    /// 读取已分配的令牌，没有时生成新的令牌。返回的布尔值表示令牌是否是新生成的。
+    fn token(&self, req: &Request) -> Result<(String, bool), BoxError> {
+        let token = match self.config.storage {
+            Storage::Cookie => CookieJar::from_headers(req.headers())
+                .get(&self.config.cookie_name())
+                .map(|cookie| cookie.value().to_owned()),
+            #[cfg(feature = "session")]
+            Storage::Session => {
+                let session = session(req)?;
+                session.get::<String>(SESSION_KEY)?
+            }
+        };
+
+        match token.filter(|token| !token.is_empty()) {
+            Some(token) => Ok((token, false)),
+            None => {
+                let token = random_token();
+                #[cfg(feature = "session")]
+                if self.config.storage == Storage::Session {
+                    session(req)?.insert(SESSION_KEY, &token)?;
+                }
+                Ok((token, true))
+            }
+        }
+    }
+
+    fn check_origin(&self, req: &Request) -> Result<(), CsrfError> {
+        let headers = req.headers();
+        let fetch_site = headers.get(SEC_FETCH_SITE);
+        if fetch_site.is_some_and(|site| site == "same-origin" || site == "none") {
+            return Ok(());
+        }
+
+        let Some(origin) = headers.get(header::ORIGIN) else {
+            return match fetch_site {
+                Some(_) => Err(CsrfError::CrossOrigin),
+                None => Ok(()),
+            };
+        };
+        if self.config.trusted_origins.contains(origin) {
+            return Ok(());
+        }
+
+        let host = headers
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .or_else(|| req.uri().authority().map(|authority| authority.as_str()));
+        let scheme = match req.uri().scheme_str() {
+            Some(scheme) => scheme,
+            None if self.config.secure => "https",
+            None => "http",
+        };
+        let origin_host = origin.to_str().ok().and_then(|origin| {
+            let (origin_scheme, host) = origin.split_once("://")?;
+            origin_scheme.eq_ignore_ascii_case(scheme).then_some(host)
+        });
+        match (origin_host, host) {
+            (Some(origin), Some(host)) if origin.eq_ignore_ascii_case(host) => Ok(()),
+            _ => Err(CsrfError::CrossOrigin),
+        }
+    }
+
+    /// 读取请求头或表单中提交的令牌，读取表单后会将请求体放回请求中。
+    async fn submitted(&self, req: &mut Request) -> Result<Option<String>, BoxError> {
+        if let Some(value) = req.headers().get(&self.config.header) {
+            return Ok(value.to_str().ok().map(ToOwned::to_owned));
+        }
+
+        if !has_content_type(req, &mime::APPLICATION_WWW_FORM_URLENCODED) {
+            return Ok(None);
+        }
+
+        let body = crate::extract::bytes(req).await?;
+        let token = form_urlencoded::parse(&body)
+            .find(|(name, _)| *name == self.config.field)
+            .map(|(_, value)| value.into_owned());
+        *req.body_mut() = BoxBody::new(body);
+        Ok(token)
+    }
+}
+
+impl<S> Service<Request> for Csrf<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request) -> Self::Future<'_> {
+        Box::pin(async move {
+            let (token, generated) = self.token(&req)?;
+
+            let result = if is_safe(req.method()) {
+                Ok(())
+            } else if let Err(e) = self.check_origin(&req) {
+                Err(e)
+            } else if generated {
+                Err(CsrfError::MissingToken)
+            } else {
+                match self.submitted(&mut req).await? {
+                    Some(submitted) if constant_time_eq(&submitted, &token) => Ok(()),
+                    Some(_) => Err(CsrfError::InvalidToken),
+                    None => Err(CsrfError::MissingToken),
+                }
+            };
+
+            let mut res = match result {
+                Ok(()) => {
+                    req.extensions_mut().insert(CsrfToken(token.clone()));
+                    self.service.call(req).await.map_err(Into::into)?
+                }
+                Err(e) => e.into_response(),
+            };
+
+            if generated && self.config.storage == Storage::Cookie {
+                set_cookie(&mut res, &self.config.cookie(token))?;
+            }
+            Ok(res)
+        })
+    }
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(feature = "session")]
+const SESSION_KEY: &str = "_csrf_token";
+
+#[cfg(feature = "session")]
+fn session(req: &Request) -> Result<&super::Session, ExtractExtensionError> {
+    req.extensions()
+        .get::<super::Session>()
+        .ok_or(ExtractExtensionError::MissingExtension {
+            type_name: std::any::type_name::<super::Session>(),
+        })
+}
+
+/// 当前客户端的 CSRF 令牌，由 [`csrf`] 中间件保存在请求扩展中。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CsrfToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequestParts for CsrfToken {
+    type Error = ExtractExtensionError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(parts.extensions.get::<CsrfToken>().cloned().ok_or(
+            ExtractExtensionError::MissingExtension {
+                type_name: std::any::type_name::<CsrfToken>(),
+            },
+        ))
+    }
+}
+
+/// CSRF 检查失败的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrfError {
+    /// 请求来自不受信任的源。
+    CrossOrigin,
+    /// 请求没有提交令牌，或者客户端还没有分配令牌。
+    MissingToken,
+    /// 提交的令牌与分配的令牌不一致。
+    InvalidToken,
+}
+
+impl fmt::Display for CsrfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsrfError::CrossOrigin => f.write_str("cross-origin request rejected"),
+            CsrfError::MissingToken => f.write_str("missing CSRF token"),
+            CsrfError::InvalidToken => f.write_str("invalid CSRF token"),
+        }
+    }
+}
+
+impl std::error::Error for CsrfError {}
+
+impl IntoResponse for CsrfError {
+    fn into_response(self) -> Response {
+        StatusCode::FORBIDDEN.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::body::BodyExt;
+    use echo_core::http::header::{CONTENT_TYPE, COOKIE, HOST, ORIGIN, SET_COOKIE};
+    use echo_core::service::service_fn;
+
+    use super::*;
+
+    /// 返回令牌和请求体。
+    async fn echo(mut req: Request) -> Result<Response, BoxError> {
+        let token = req.extensions().get::<CsrfToken>().unwrap().clone();
+        let body = crate::extract::bytes(&mut req).await?;
+        let body = String::from_utf8(body.to_vec())?;
+        Ok(format!("{token} {body}").into_response())
+    }
+
+    fn request(method: Method, headers: &[(HeaderName, &str)], body: &str) -> Request {
+        let mut req = Request::builder()
+            .method(method)
+            .header(HOST, "example.com");
+        for (name, value) in headers {
+            req = req.header(name, *value);
+        }
+        req.body(BoxBody::new(body.to_owned())).unwrap()
+    }
+
+    fn new_cookie(res: &Response) -> Option<String> {
+        let value = res.headers().get(SET_COOKIE)?.to_str().unwrap();
+        Some(value.split(';').next().unwrap().to_owned())
+    }
+
+    async fn body(res: Response) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    /// 通过 `GET` 请求分配令牌，返回 Cookie 和令牌。
+    async fn assign(svc: &impl Service<Request, Response = Response>) -> (String, String) {
+        let res = svc.call(request(Method::GET, &[], "")).await.ok().unwrap();
+        let cookie = new_cookie(&res).unwrap();
+        let token = cookie.split_once('=').unwrap().1.to_owned();
+        (cookie, token)
+    }
+
+    #[tokio::test]
+    async fn safe_methods() {
+        let svc = csrf().transform(service_fn(echo));
+
+        let res = svc.call(request(Method::GET, &[], "")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let value = res.headers()[SET_COOKIE].to_str().unwrap();
+        assert!(value.starts_with("__Host-csrf_token="));
+        assert!(value.contains("Secure"));
+        assert!(value.contains("HttpOnly"));
+        assert!(value.contains("Path=/"));
+
+        // 已经分配了令牌时沿用该令牌。
+        let (cookie, token) = assign(&svc).await;
+        let res = svc
+            .call(request(Method::HEAD, &[(COOKIE, &cookie)], ""))
+            .await
+            .unwrap();
+        assert!(new_cookie(&res).is_none());
+        assert_eq!(body(res).await, format!("{token} "));
+
+        let svc = csrf().secure(false).transform(service_fn(echo));
+        let (cookie, _) = assign(&svc).await;
+        assert!(cookie.starts_with("csrf_token="));
+    }
+
+    #[tokio::test]
+    async fn token() {
+        let svc = csrf().transform(service_fn(echo));
+        let (cookie, token) = assign(&svc).await;
+
+        // 没有分配令牌。
+        let res = svc
+            .call(request(Method::POST, &[(X_CSRF_TOKEN, &token)], ""))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(new_cookie(&res).is_some());
+
+        // 没有提交令牌。
+        let res = svc
+            .call(request(Method::POST, &[(COOKIE, &cookie)], ""))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = svc
+            .call(request(
+                Method::POST,
+                &[(COOKIE, &cookie), (X_CSRF_TOKEN, "wrong")],
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = svc
+            .call(request(
+                Method::DELETE,
+                &[(COOKIE, &cookie), (X_CSRF_TOKEN, &token)],
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn form() {
+        let svc = csrf().transform(service_fn(echo));
+        let (cookie, token) = assign(&svc).await;
+        let form = "application/x-www-form-urlencoded";
+
+        let res = svc
+            .call(request(
+                Method::POST,
+                &[(COOKIE, &cookie), (CONTENT_TYPE, form)],
+                "title=hello&_csrf=wrong",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        // 读取表单后请求体被放回请求中。
+        let body = format!("title=hello&_csrf={token}");
+        let res = svc
+            .call(request(
+                Method::POST,
+                &[(COOKIE, &cookie), (CONTENT_TYPE, form)],
+                &body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(self::body(res).await, format!("{token} {body}"));
+
+        // 只读取 `application/x-www-form-urlencoded` 表单。
+        let res = svc
+            .call(request(
+                Method::POST,
+                &[(COOKIE, &cookie), (CONTENT_TYPE, "text/plain")],
+                &body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn origin() {
+        let svc = csrf()
+            .trusted_origins([HeaderValue::from_static("https://app.example.com")])
+            .transform(service_fn(echo));
+        let (cookie, token) = assign(&svc).await;
+        let post = |headers: &[(HeaderName, &str)]| {
+            let mut headers = headers.to_vec();
+            headers.extend([(COOKIE, cookie.as_str()), (X_CSRF_TOKEN, token.as_str())]);
+            svc.call(request(Method::POST, &headers, ""))
+        };
+
+        let status = |res: Result<Response, BoxError>| res.unwrap().status();
+        assert_eq!(status(post(&[]).await), StatusCode::OK);
+        assert_eq!(
+            status(post(&[(ORIGIN, "https://example.com")]).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(post(&[(ORIGIN, "https://app.example.com")]).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(post(&[(SEC_FETCH_SITE, "same-origin")]).await),
+            StatusCode::OK
+        );
+
+        for headers in [
+            &[(ORIGIN, "https://evil.com")][..],
+            &[(ORIGIN, "http://example.com")],
+            &[(ORIGIN, "null")],
+            &[(SEC_FETCH_SITE, "cross-site")],
+            &[
+                (SEC_FETCH_SITE, "same-site"),
+                (ORIGIN, "https://evil.example.com"),
+            ],
+        ] {
+            assert_eq!(status(post(headers).await), StatusCode::FORBIDDEN);
+        }
+
+        let svc = csrf().secure(false).transform(service_fn(echo));
+        let (cookie, token) = assign(&svc).await;
+        let headers = [
+            (COOKIE, cookie.as_str()),
+            (X_CSRF_TOKEN, token.as_str()),
+            (ORIGIN, "http://example.com"),
+        ];
+        let res = svc.call(request(Method::POST, &headers, "")).await;
+        assert_eq!(status(res), StatusCode::OK);
+    }
+
+    #[cfg(feature = "session")]
+    #[tokio::test]
+    async fn session() {
+        use crate::middleware::{session, MemoryStore};
+
+        let svc = (session(MemoryStore::new()), csrf().session()).transform(service_fn(echo));
+
+        let res = svc.call(request(Method::GET, &[], "")).await.unwrap();
+        let cookie = new_cookie(&res).unwrap();
+        assert!(cookie.starts_with("session_id="));
+        let token = body(res).await.trim().to_owned();
+
+        let res = svc
+            .call(request(
+                Method::POST,
+                &[(COOKIE, &cookie), (X_CSRF_TOKEN, &token)],
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // 令牌与会话绑定。
+        let res = svc
+            .call(request(Method::POST, &[(X_CSRF_TOKEN, &token)], ""))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
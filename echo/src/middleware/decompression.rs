@@ -0,0 +1,315 @@
+use std::{fmt, io};
+
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+use echo_core::body::{BodyExt, BoxBody, Frame, StreamBody};
+use echo_core::http::header::{self, HeaderValue};
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{Request, Response};
+use futures_util::TryStreamExt;
+use tokio::io::{AsyncBufRead, AsyncRead};
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use super::encoding::{Encoding, Encodings};
+use super::DEFAULT_BODY_LIMIT;
+
+/// 根据 `Content-Encoding` 透明地解压请求体。
+///
+/// 支持 `gzip`、`deflate`、`br` 和 `zstd`，解压后会移除 `Content-Encoding` 和 `Content-Length`。
+/// 遇到未启用或不支持的编码时返回 `415 Unsupported Media Type`，并通过 `Accept-Encoding`
+/// 告知客户端支持的编码。
+///
+/// 解压后的大小默认不能超过 [`DEFAULT_BODY_LIMIT`]，超过时读取请求体会返回
+/// [`DataTooLarge`](crate::body::DataTooLarge) 错误。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::decompression;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(decompression().br(false).max_size(16 * 1024 * 1024));
+/// ```
+#[inline]
+pub fn decompression() -> DecompressionMiddleware {
+    DecompressionMiddleware::new()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DecompressionMiddleware {
+    encodings: Encodings,
+    max_size: Option<usize>,
+}
+
+impl DecompressionMiddleware {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            encodings: Default::default(),
+            max_size: Some(DEFAULT_BODY_LIMIT),
+        }
+    }
+
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.encodings.gzip = enable;
+        self
+    }
+
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.encodings.deflate = enable;
+        self
+    }
+
+    pub fn br(mut self, enable: bool) -> Self {
+        self.encodings.br = enable;
+        self
+    }
+
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.encodings.zstd = enable;
+        self
+    }
+
+    /// 设置解压后请求体的最大大小（字节），用于防范压缩炸弹。
+    pub fn max_size(mut self, max: usize) -> Self {
+        self.max_size = Some(max);
+        self
+    }
+
+    /// 不限制解压后请求体的大小。
+    pub fn unlimited(mut self) -> Self {
+        self.max_size = None;
+        self
+    }
+}
+
+impl Default for DecompressionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for DecompressionMiddleware {
+    type Service = Decompression<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Decompression {
+            service,
+            encodings: self.encodings,
+            max_size: self.max_size,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Decompression<S> {
+    service: S,
+    encodings: Encodings,
+    max_size: Option<usize>,
+}
+
+impl<S> Decompression<S> {
+    /// 解析 `Content-Encoding`，按编码的逆序返回需要依次应用的解码器。
+    fn decoders(&self, req: &Request) -> Result<Vec<Encoding>, ()> {
+        let mut decoders = Vec::new();
+        for value in req.headers().get_all(header::CONTENT_ENCODING) {
+            let value = value.to_str().map_err(|_| ())?;
+            for name in value.split(',').map(str::trim) {
+                if name.is_empty() || name.eq_ignore_ascii_case("identity") {
+                    continue;
+                }
+                match Encoding::from_name(name) {
+                    Some(encoding) if self.encodings.contains(encoding) => decoders.push(encoding),
+                    _ => return Err(()),
+                }
+            }
+        }
+        decoders.reverse();
+        Ok(decoders)
+    }
+
+    fn unsupported(&self) -> Response {
+        let accept = self
+            .encodings
+            .iter()
+            .map(Encoding::as_str)
+            .chain(["identity"])
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut res = Response::new(().boxed());
+        *res.status_mut() = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+        if let Ok(value) = HeaderValue::try_from(accept) {
+            res.headers_mut().insert(header::ACCEPT_ENCODING, value);
+        }
+        res
+    }
+}
+
+impl<S> Service<Request> for Decompression<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future<'f> = BoxFuture<'f, Result<Response, S::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request) -> Self::Future<'_> {
+        let decoders = match self.decoders(&req) {
+            Ok(decoders) => decoders,
+            Err(()) => {
+                let res = self.unsupported();
+                return Box::pin(async move { Ok(res) });
+            }
+        };
+
+        if !decoders.is_empty() {
+            let headers = req.headers_mut();
+            headers.remove(header::CONTENT_ENCODING);
+            headers.remove(header::CONTENT_LENGTH);
+
+            let body = decoders
+                .into_iter()
+                .fold(std::mem::take(req.body_mut()), decode);
+            *req.body_mut() = match self.max_size {
+                Some(max) => body.limit(max).boxed(),
+                None => body,
+            };
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+impl<S> fmt::Debug for Decompression<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decompression")
+            .field("service", &self.service)
+            .field("encodings", &self.encodings)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+fn decode(body: BoxBody, encoding: Encoding) -> BoxBody {
+    let reader = into_reader(body);
+    match encoding {
+        Encoding::Gzip => {
+            let mut decoder = GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            from_reader(decoder)
+        }
+        Encoding::Deflate => from_reader(ZlibDecoder::new(reader)),
+        Encoding::Brotli => from_reader(BrotliDecoder::new(reader)),
+        Encoding::Zstd => from_reader(ZstdDecoder::new(reader)),
+    }
+}
+
+/// 将请求体转换为 `AsyncBufRead`，忽略非数据帧。
+fn into_reader(body: BoxBody) -> impl AsyncBufRead + Send {
+    StreamReader::new(
+        body.stream()
+            .try_filter_map(|frame| async move { Ok(frame.into_data().ok()) })
+            .map_err(io::Error::other),
+    )
+}
+
+/// 将 `AsyncRead` 转换为 `BoxBody`。
+fn from_reader<R>(reader: R) -> BoxBody
+where
+    R: AsyncRead + Send + 'static,
+{
+    StreamBody::new(ReaderStream::new(reader).map_ok(Frame::data)).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder};
+    use echo_core::body::{BodyExt, BoxBody, DataTooLarge};
+    use echo_core::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{BoxError, Request, Response};
+    use tokio::io::{AsyncRead, AsyncReadExt};
+
+    use super::decompression;
+
+    const TEXT: &str = "hello, world! hello, world! hello, world!";
+
+    async fn echo(mut req: Request) -> Result<Response, BoxError> {
+        assert!(!req.headers().contains_key(CONTENT_ENCODING));
+        let body = crate::extract::bytes(&mut req).await?;
+        Ok(body.into_response())
+    }
+
+    async fn read(mut reader: impl AsyncRead + Unpin) -> Vec<u8> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+        buf
+    }
+
+    fn request(encoding: &'static str, body: Vec<u8>) -> Request {
+        Request::builder()
+            .header(CONTENT_ENCODING, encoding)
+            .body(BoxBody::new(body))
+            .unwrap()
+    }
+
+    async fn body(res: Response) -> Vec<u8> {
+        res.into_body().collect().await.unwrap().to_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let svc = decompression().transform(service_fn(echo));
+
+        let gzip = read(GzipEncoder::new(TEXT.as_bytes())).await;
+        let res = svc.call(request("gzip", gzip)).await.unwrap();
+        assert_eq!(body(res).await, TEXT.as_bytes());
+
+        let br = read(BrotliEncoder::new(TEXT.as_bytes())).await;
+        let res = svc.call(request("br", br.clone())).await.unwrap();
+        assert_eq!(body(res).await, TEXT.as_bytes());
+
+        // 先使用 br 再使用 gzip 编码。
+        let both = read(GzipEncoder::new(&br[..])).await;
+        let res = svc.call(request("br, gzip", both)).await.unwrap();
+        assert_eq!(body(res).await, TEXT.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn unsupported() {
+        let svc = decompression().br(false).transform(service_fn(echo));
+
+        let res = svc.call(request("compress", vec![])).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            res.headers()[ACCEPT_ENCODING],
+            "zstd, gzip, deflate, identity"
+        );
+
+        let br = read(BrotliEncoder::new(TEXT.as_bytes())).await;
+        let res = svc.call(request("br", br)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn max_size() {
+        let svc = decompression().max_size(10).transform(service_fn(echo));
+
+        let gzip = read(GzipEncoder::new(TEXT.as_bytes())).await;
+        let e = svc.call(request("gzip", gzip)).await.unwrap_err();
+        assert!(crate::util::find_source::<DataTooLarge>(&*e).is_some());
+    }
+}
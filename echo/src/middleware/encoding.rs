@@ -0,0 +1,77 @@
+//! 压缩与解压中间件共用的内容编码。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl Encoding {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let encoding = if name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip") {
+            Encoding::Gzip
+        } else if name.eq_ignore_ascii_case("deflate") {
+            Encoding::Deflate
+        } else if name.eq_ignore_ascii_case("br") {
+            Encoding::Brotli
+        } else if name.eq_ignore_ascii_case("zstd") {
+            Encoding::Zstd
+        } else {
+            return None;
+        };
+        Some(encoding)
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+}
+
+/// 启用的编码集合。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Encodings {
+    pub(crate) gzip: bool,
+    pub(crate) deflate: bool,
+    pub(crate) br: bool,
+    pub(crate) zstd: bool,
+}
+
+impl Encodings {
+    pub(crate) fn contains(self, encoding: Encoding) -> bool {
+        match encoding {
+            Encoding::Gzip => self.gzip,
+            Encoding::Deflate => self.deflate,
+            Encoding::Brotli => self.br,
+            Encoding::Zstd => self.zstd,
+        }
+    }
+
+    pub(crate) fn iter(self) -> impl Iterator<Item = Encoding> {
+        [
+            Encoding::Zstd,
+            Encoding::Brotli,
+            Encoding::Gzip,
+            Encoding::Deflate,
+        ]
+        .into_iter()
+        .filter(move |encoding| self.contains(*encoding))
+    }
+}
+
+impl Default for Encodings {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            deflate: true,
+            br: true,
+            zstd: true,
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt;
+use std::future::Future;
+
+use echo_core::middleware::Middleware;
+use echo_core::response::{IntoResponse, IntoResponseResult};
+use echo_core::service::future::BoxFuture;
+use echo_core::service::{ArcService, Service};
+use echo_core::{BoxError, Request, Response};
+
+use crate::route::Router;
+
+/// 使用异步闭包创建中间件。
+///
+/// 闭包接收请求和 [`Next`]，调用 [`Next::run`] 执行内层服务，因此可以在前后修改请求和响应，
+/// 也可以直接返回响应而不调用内层服务。闭包的返回值需实现 [`IntoResponseResult`]。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::{from_fn, Next};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+/// use echo::http::{header, HeaderValue, StatusCode};
+/// use echo::response::IntoResponse;
+/// use echo::{BoxError, Request, Response};
+///
+/// let app = Router::new().with(from_fn(|req: Request, next: Next| async move {
+///     if !req.headers().contains_key(header::AUTHORIZATION) {
+///         return Ok::<Response, BoxError>(StatusCode::UNAUTHORIZED.into_response());
+///     }
+///     let mut res = next.run(req).await?;
+///     res.headers_mut()
+///         .insert("x-powered-by", HeaderValue::from_static("echo"));
+///     Ok(res)
+/// }));
+/// ```
+#[inline]
+pub fn from_fn<F>(f: F) -> FromFnMiddleware<F, ()> {
+    FromFnMiddleware { f, state: () }
+}
+
+/// 与 [`from_fn`] 相同，但是闭包的第一个参数为 `state` 的克隆。
+///
+/// # 例子
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use echo::middleware::{from_fn_with_state, Next};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+/// use echo::Request;
+///
+/// let counter = Arc::new(AtomicUsize::new(0));
+/// let app = Router::new().with(from_fn_with_state(
+///     counter,
+///     |counter: Arc<AtomicUsize>, req: Request, next: Next| async move {
+///         counter.fetch_add(1, Ordering::Relaxed);
+///         next.run(req).await
+///     },
+/// ));
+/// ```
+#[inline]
+pub fn from_fn_with_state<F, T>(state: T, f: F) -> FromFnMiddleware<F, WithState<T>> {
+    FromFnMiddleware {
+        f,
+        state: WithState(state),
+    }
+}
+
+/// [`from_fn_with_state`] 使用的状态。
+#[derive(Debug, Clone, Copy)]
+pub struct WithState<T>(T);
+
+/// 可以作为 [`from_fn`] 或 [`from_fn_with_state`] 参数的闭包。
+pub trait FromFnHandler<T>: Send + Sync {
+    fn call(
+        &self,
+        state: &T,
+        req: Request,
+        next: Next,
+    ) -> BoxFuture<'static, Result<Response, BoxError>>;
+}
+
+impl<F, Fut, R> FromFnHandler<()> for F
+where
+    F: Fn(Request, Next) -> Fut + Send + Sync,
+    Fut: Future<Output = R> + Send + 'static,
+    R: IntoResponseResult,
+{
+    fn call(
+        &self,
+        _: &(),
+        req: Request,
+        next: Next,
+    ) -> BoxFuture<'static, Result<Response, BoxError>> {
+        let fut = self(req, next);
+        Box::pin(async move { fut.await.into_response_result() })
+    }
+}
+
+impl<F, Fut, R, T> FromFnHandler<WithState<T>> for F
+where
+    F: Fn(T, Request, Next) -> Fut + Send + Sync,
+    Fut: Future<Output = R> + Send + 'static,
+    R: IntoResponseResult,
+    T: Clone + Send + Sync,
+{
+    fn call(
+        &self,
+        state: &WithState<T>,
+        req: Request,
+        next: Next,
+    ) -> BoxFuture<'static, Result<Response, BoxError>> {
+        let fut = self(state.0.clone(), req, next);
+        Box::pin(async move { fut.await.into_response_result() })
+    }
+}
+
+/// 内层服务的句柄。
+#[derive(Clone)]
+pub struct Next {
+    service: ArcService<Request, Response, BoxError>,
+}
+
+impl Next {
+    /// 使用 `req` 调用内层服务。
+    pub async fn run(self, req: Request) -> Result<Response, BoxError> {
+        self.service.call(req).await
+    }
+}
+
+impl fmt::Debug for Next {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next").finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct FromFnMiddleware<F, T> {
+    f: F,
+    state: T,
+}
+
+impl<S, F, T> Middleware<S> for FromFnMiddleware<F, T>
+where
+    S: Service<Request> + Send + Sync + 'static,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+{
+    type Service = FromFn<F, T>;
+
+    fn transform(self, service: S) -> Self::Service {
+        FromFn {
+            next: Next {
+                service: Router::into_arc_service(service),
+            },
+            f: self.f,
+            state: self.state,
+        }
+    }
+}
+
+impl<F, T> fmt::Debug for FromFnMiddleware<F, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromFnMiddleware")
+            .field("f", &std::any::type_name::<F>())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct FromFn<F, T> {
+    next: Next,
+    f: F,
+    state: T,
+}
+
+impl<F, T> Service<Request> for FromFn<F, T>
+where
+    F: FromFnHandler<T>,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'static, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    #[inline]
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        self.f.call(&self.state, req, self.next.clone())
+    }
+}
+
+impl<F, T> fmt::Debug for FromFn<F, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromFn")
+            .field("next", &self.next)
+            .field("f", &std::any::type_name::<F>())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::{fmt, io};
+
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::http::{HeaderValue, StatusCode};
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, ArcService, Service, ServiceExt};
+    use echo_core::{BoxError, Request, Response};
+
+    use super::{from_fn, from_fn_with_state, Next};
+
+    /// 返回 `x-name` 请求头的内层服务，并记录调用次数。
+    fn inner(calls: Arc<AtomicUsize>) -> ArcService<Request, Response, io::Error> {
+        service_fn(move |req: Request| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            let name = req
+                .headers()
+                .get("x-name")
+                .map(|value| value.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            async move { Ok(name.into_response()) }
+        })
+        .boxed_arc()
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn early_return() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = from_fn(|_: Request, _: Next| async { StatusCode::UNAUTHORIZED })
+            .transform(inner(calls.clone()));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn modify_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = from_fn(|req: Request, next: Next| async move {
+            let mut res = next.run(req).await?;
+            res.headers_mut()
+                .insert("x-powered-by", HeaderValue::from_static("echo"));
+            Ok::<_, BoxError>(res)
+        })
+        .transform(inner(calls.clone()));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.headers()["x-powered-by"], "echo");
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn modify_request() {
+        let svc = from_fn(|mut req: Request, next: Next| async move {
+            req.headers_mut()
+                .insert("x-name", HeaderValue::from_static("echo"));
+            next.run(req).await
+        })
+        .transform(inner(Default::default()));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(body(res).await, "echo");
+    }
+
+    #[tokio::test]
+    async fn with_state() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let svc = from_fn_with_state(
+            counter.clone(),
+            |counter: Arc<AtomicUsize>, mut req: Request, next: Next| async move {
+                let n = counter.fetch_add(1, Ordering::Relaxed);
+                req.headers_mut().insert("x-name", n.into());
+                next.run(req).await
+            },
+        )
+        .transform(inner(Default::default()));
+
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(body(res).await, "0");
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(body(res).await, "1");
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn error() {
+        #[derive(Debug)]
+        struct InnerError;
+
+        impl fmt::Display for InnerError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("inner error")
+            }
+        }
+
+        impl std::error::Error for InnerError {}
+
+        let svc = from_fn(|req: Request, next: Next| async move {
+            let res = next.run(req).await?;
+            Ok::<_, BoxError>(res)
+        })
+        .transform(service_fn(|_: Request| async {
+            Err::<Response, _>(InnerError)
+        }));
+        let e = svc.call(Request::default()).await.unwrap_err();
+        assert!(e.is::<InnerError>());
+    }
+}
@@ -0,0 +1,712 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use echo_core::body::{Body, BodyExt, BoxBody, Bytes, Frame, SizeHint};
+use echo_core::http::header::{self, HeaderValue};
+use echo_core::http::{Method, StatusCode};
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+use crate::route::{MatchedPath, RouteError, RouteErrorKind};
+
+const DEFAULT_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const DEFAULT_SIZE_BUCKETS: &[f64] = &[
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+];
+
+/// 没有匹配到路由时 `route` 标签的值。
+const UNMATCHED_ROUTE: &str = "";
+
+/// 将请求指标记录到 [`Metrics`] 中。
+///
+/// 记录的指标如下，其中 `route` 为匹配的路由模板（参见 [`MatchedPath`]），没有匹配到路由时为空，
+/// 非标准的请求方法统一记为 `OTHER`：
+///
+/// - `http_requests_total{method, route, status}`：请求总数；
+/// - `http_requests_in_flight{method}`：正在处理的请求数；
+/// - `http_request_duration_seconds{method, route, status}`：返回响应的耗时；
+/// - `http_response_size_bytes{method, route, status}`：响应体的大小，在响应体写入完成时记录。
+///
+/// 内部服务返回错误时，[`RouteError`] 按照 `404` 或 `405` 记录，其他错误按照 `500` 记录。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::{metrics, Metrics};
+/// use echo::route::{get, Router};
+/// use echo::service::ServiceExt;
+///
+/// let registry = Metrics::new().namespace("app");
+///
+/// let app = Router::new()
+///     .route("/metrics", get(registry.clone()))
+///     .with(metrics(registry));
+/// ```
+#[inline]
+pub fn metrics(metrics: Metrics) -> MetricsMiddleware {
+    MetricsMiddleware::new(metrics)
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricsMiddleware {
+    metrics: Metrics,
+}
+
+impl MetricsMiddleware {
+    #[inline]
+    pub fn new(metrics: Metrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Middleware<S> for MetricsMiddleware {
+    type Service = MetricsService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        MetricsService {
+            service,
+            metrics: self.metrics,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricsService<S> {
+    service: S,
+    metrics: Metrics,
+}
+
+impl<S> Service<Request> for MetricsService<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let start = Instant::now();
+        let method = method_label(req.method());
+        let in_flight = InFlight::new(&self.metrics, method);
+
+        Box::pin(async move {
+            let res = self.service.call(req).await.map_err(Into::into);
+            let elapsed = start.elapsed().as_secs_f64();
+            drop(in_flight);
+
+            match res {
+                Ok(res) => {
+                    let route = match res.extensions().get::<MatchedPath>() {
+                        Some(matched) => matched.as_str().into(),
+                        None => UNMATCHED_ROUTE.into(),
+                    };
+                    let key = Key {
+                        route,
+                        method,
+                        status: res.status().as_u16(),
+                    };
+                    self.metrics.observe_request(&key, elapsed);
+
+                    let recorder = SizeRecorder {
+                        metrics: self.metrics.clone(),
+                        key,
+                        size: 0,
+                        finished: false,
+                    };
+                    Ok(res.map(|body| MetricsBody { body, recorder }.boxed()))
+                }
+                Err(e) => {
+                    let status = match crate::util::find_source::<RouteError>(&*e) {
+                        Some(e) if e.kind() == RouteErrorKind::NotFound => StatusCode::NOT_FOUND,
+                        Some(_) => StatusCode::METHOD_NOT_ALLOWED,
+                        None => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    let key = Key {
+                        route: UNMATCHED_ROUTE.into(),
+                        method,
+                        status: status.as_u16(),
+                    };
+                    self.metrics.observe_request(&key, elapsed);
+                    Err(e)
+                }
+            }
+        })
+    }
+}
+
+/// 请求指标的注册表，同时也是以 Prometheus 文本格式返回指标的服务。
+///
+/// 克隆的注册表共享相同的数据，配置方法会清空已有的数据，应在共享之前调用。
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    namespace: String,
+    duration_buckets: Vec<f64>,
+    size_buckets: Vec<f64>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    series: BTreeMap<Key, Series>,
+    in_flight: BTreeMap<&'static str, i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    route: Arc<str>,
+    method: &'static str,
+    status: u16,
+}
+
+struct Series {
+    requests: u64,
+    duration: Histogram,
+    size: Histogram,
+}
+
+struct Histogram {
+    /// 每个桶单独计数，输出时累加。
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::from_parts(
+            String::new(),
+            DEFAULT_DURATION_BUCKETS.to_vec(),
+            DEFAULT_SIZE_BUCKETS.to_vec(),
+        )
+    }
+
+    /// 设置指标名称的前缀，例如 `app` 对应 `app_http_requests_total`。
+    pub fn namespace(self, namespace: impl Into<String>) -> Self {
+        Self::from_parts(
+            namespace.into(),
+            self.inner.duration_buckets.clone(),
+            self.inner.size_buckets.clone(),
+        )
+    }
+
+    /// 设置 `http_request_duration_seconds` 的桶（秒）。
+    pub fn duration_buckets(self, buckets: impl Into<Vec<f64>>) -> Self {
+        Self::from_parts(
+            self.inner.namespace.clone(),
+            buckets.into(),
+            self.inner.size_buckets.clone(),
+        )
+    }
+
+    /// 设置 `http_response_size_bytes` 的桶（字节）。
+    pub fn size_buckets(self, buckets: impl Into<Vec<f64>>) -> Self {
+        Self::from_parts(
+            self.inner.namespace.clone(),
+            self.inner.duration_buckets.clone(),
+            buckets.into(),
+        )
+    }
+
+    fn from_parts(namespace: String, mut duration: Vec<f64>, mut size: Vec<f64>) -> Self {
+        duration.sort_by(f64::total_cmp);
+        duration.dedup();
+        size.sort_by(f64::total_cmp);
+        size.dedup();
+
+        Self {
+            inner: Arc::new(Inner {
+                namespace,
+                duration_buckets: duration,
+                size_buckets: size,
+                state: Default::default(),
+            }),
+        }
+    }
+
+    /// 以 Prometheus 文本格式输出所有指标。
+    pub fn render(&self) -> String {
+        let inner = &*self.inner;
+        let state = self.lock();
+        let mut out = String::new();
+
+        let name = inner.name("http_requests_total");
+        write_header(&mut out, &name, "Total number of HTTP requests.", "counter");
+        for (key, series) in &state.series {
+            let _ = writeln!(out, "{name}{{{}}} {}", key.labels(), series.requests);
+        }
+
+        let name = inner.name("http_requests_in_flight");
+        write_header(
+            &mut out,
+            &name,
+            "Number of HTTP requests currently being handled.",
+            "gauge",
+        );
+        for (method, count) in &state.in_flight {
+            let _ = writeln!(out, "{name}{{method=\"{method}\"}} {count}");
+        }
+
+        let name = inner.name("http_request_duration_seconds");
+        write_header(
+            &mut out,
+            &name,
+            "Time spent until the HTTP response headers are returned.",
+            "histogram",
+        );
+        for (key, series) in &state.series {
+            series
+                .duration
+                .render(&mut out, &name, &key.labels(), &inner.duration_buckets);
+        }
+
+        let name = inner.name("http_response_size_bytes");
+        write_header(
+            &mut out,
+            &name,
+            "Size of HTTP response bodies.",
+            "histogram",
+        );
+        for (key, series) in &state.series {
+            if series.size.count > 0 {
+                series
+                    .size
+                    .render(&mut out, &name, &key.labels(), &inner.size_buckets);
+            }
+        }
+
+        out
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn observe_request(&self, key: &Key, duration: f64) {
+        let inner = &*self.inner;
+        let mut state = self.lock();
+        let series = state.series.entry(key.clone()).or_insert_with(|| Series {
+            requests: 0,
+            duration: Histogram::new(&inner.duration_buckets),
+            size: Histogram::new(&inner.size_buckets),
+        });
+        series.requests += 1;
+        series.duration.observe(&inner.duration_buckets, duration);
+    }
+
+    fn observe_size(&self, key: &Key, size: u64) {
+        let inner = &*self.inner;
+        if let Some(series) = self.lock().series.get_mut(key) {
+            series.size.observe(&inner.size_buckets, size as f64);
+        }
+    }
+
+    fn add_in_flight(&self, method: &'static str, delta: i64) {
+        *self.lock().in_flight.entry(method).or_default() += delta;
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics")
+            .field("namespace", &self.inner.namespace)
+            .field("duration_buckets", &self.inner.duration_buckets)
+            .field("size_buckets", &self.inner.size_buckets)
+            .finish()
+    }
+}
+
+impl<B> Service<Request<B>> for Metrics {
+    type Response = Response;
+    type Error = std::convert::Infallible;
+    type Future<'f> = std::future::Ready<Result<Response, Self::Error>>
+    where
+        Self: 'f;
+
+    fn call(&self, _req: Request<B>) -> Self::Future<'_> {
+        let mut res = Response::new(BoxBody::new(self.render().into_bytes()));
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+        );
+        std::future::ready(Ok(res))
+    }
+}
+
+impl Inner {
+    fn name(&self, name: &str) -> String {
+        if self.namespace.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}_{name}", self.namespace)
+        }
+    }
+}
+
+impl Key {
+    fn labels(&self) -> String {
+        format!(
+            "method=\"{}\",route=\"{}\",status=\"{}\"",
+            self.method,
+            escape(&self.route),
+            self.status
+        )
+    }
+}
+
+impl Histogram {
+    fn new(buckets: &[f64]) -> Self {
+        Self {
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, buckets: &[f64], value: f64) {
+        if let Some(i) = buckets.iter().position(|&le| value <= le) {
+            self.counts[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str, buckets: &[f64]) {
+        let mut cumulative = 0;
+        for (le, count) in buckets.iter().zip(&self.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", self.count);
+    }
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, ty: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {ty}");
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 非标准的方法统一记录为 `OTHER`，避免标签的基数无限增长。
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "OTHER",
+    }
+}
+
+struct InFlight {
+    metrics: Metrics,
+    method: &'static str,
+}
+
+impl InFlight {
+    fn new(metrics: &Metrics, method: &'static str) -> Self {
+        metrics.add_in_flight(method, 1);
+        Self {
+            metrics: metrics.clone(),
+            method,
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.metrics.add_in_flight(self.method, -1);
+    }
+}
+
+/// 响应体写入完成或被丢弃时记录响应体的大小。
+struct SizeRecorder {
+    metrics: Metrics,
+    key: Key,
+    size: u64,
+    finished: bool,
+}
+
+impl SizeRecorder {
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.metrics.observe_size(&self.key, self.size);
+        }
+    }
+}
+
+impl Drop for SizeRecorder {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct MetricsBody {
+        #[pin]
+        body: BoxBody,
+        recorder: SizeRecorder,
+    }
+}
+
+impl Body for MetricsBody {
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let this = self.project();
+        let frame = std::task::ready!(this.body.poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.recorder.size += data.len() as u64;
+                }
+            }
+            Some(Err(_)) | None => this.recorder.finish(),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use echo_core::body::StreamBody;
+    use echo_core::service::{service_fn, ServiceExt};
+    use futures_util::{stream, StreamExt};
+
+    use super::*;
+    use crate::route::{get, Router};
+
+    fn request(method: Method, uri: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(BoxBody::default())
+            .unwrap()
+    }
+
+    fn app(registry: &Metrics) -> impl Service<Request, Response = Response, Error = BoxError> {
+        let gauge = registry.clone();
+        Router::new()
+            .route(
+                "/users/:id",
+                get(service_fn(|_: Request| async {
+                    Ok::<_, BoxError>(Response::new(BoxBody::new("hello")))
+                })),
+            )
+            .route(
+                "/stream",
+                get(service_fn(|_: Request| async {
+                    let chunks = stream::iter(["hello", "world"])
+                        .map(|chunk| Ok::<_, Infallible>(Frame::data(Bytes::from(chunk))))
+                        .chain(stream::pending());
+                    Ok::<_, BoxError>(Response::new(BoxBody::new(StreamBody::new(chunks))))
+                })),
+            )
+            .route(
+                "/in-flight",
+                get(service_fn(move |_: Request| {
+                    // 在处理请求的过程中读取正在处理的请求数。
+                    let out = gauge.render();
+                    async move { Ok::<_, BoxError>(Response::new(BoxBody::new(out))) }
+                })),
+            )
+            .route("/metrics", get(registry.clone()))
+            .with(metrics(registry.clone()))
+    }
+
+    #[tokio::test]
+    async fn route_label() {
+        let registry = Metrics::new();
+        let app = app(&registry);
+
+        for id in ["1", "2"] {
+            let res = app
+                .call(request(Method::GET, &format!("/users/{id}")))
+                .await
+                .unwrap();
+            res.into_body().collect().await.unwrap();
+        }
+
+        let out = registry.render();
+        let labels = r#"method="GET",route="/users/:id",status="200""#;
+        assert!(out.contains(&format!("http_requests_total{{{labels}}} 2\n")));
+        assert!(out.contains(&format!(
+            "http_request_duration_seconds_count{{{labels}}} 2\n"
+        )));
+        assert!(!out.contains("/users/1"));
+    }
+
+    #[tokio::test]
+    async fn route_error() {
+        let registry = Metrics::new();
+        let app = app(&registry);
+
+        let e = app
+            .call(request(Method::GET, "/missing"))
+            .await
+            .unwrap_err();
+        assert!(crate::util::find_source::<RouteError>(&*e).is_some());
+        app.call(request(Method::POST, "/users/1"))
+            .await
+            .unwrap_err();
+
+        let out = registry.render();
+        assert!(out.contains("http_requests_total{method=\"GET\",route=\"\",status=\"404\"} 1\n"));
+        assert!(out.contains("http_requests_total{method=\"POST\",route=\"\",status=\"405\"} 1\n"));
+        // 出错时没有响应体，不记录响应体的大小。
+        assert!(!out.contains("http_response_size_bytes_count"));
+    }
+
+    #[tokio::test]
+    async fn in_flight() {
+        let registry = Metrics::new();
+        let app = app(&registry);
+
+        let res = app.call(request(Method::GET, "/in-flight")).await.unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let during = std::str::from_utf8(&body).unwrap();
+        assert!(during.contains("http_requests_in_flight{method=\"GET\"} 1\n"));
+
+        let out = registry.render();
+        assert!(out.contains("http_requests_in_flight{method=\"GET\"} 0\n"));
+
+        // 请求被取消时同样减少。
+        let call = app.call(request(Method::GET, "/in-flight"));
+        drop(call);
+        let out = registry.render();
+        assert!(out.contains("http_requests_in_flight{method=\"GET\"} 0\n"));
+    }
+
+    #[tokio::test]
+    async fn response_size() {
+        let registry = Metrics::new();
+        let app = app(&registry);
+
+        let res = app.call(request(Method::GET, "/users/1")).await.unwrap();
+        let labels = r#"method="GET",route="/users/:id",status="200""#;
+        // 响应体写入完成之前不记录。
+        assert!(!registry.render().contains("http_response_size_bytes_count"));
+        res.into_body().collect().await.unwrap();
+        let out = registry.render();
+        assert!(out.contains(&format!("http_response_size_bytes_sum{{{labels}}} 5\n")));
+        assert!(out.contains(&format!("http_response_size_bytes_count{{{labels}}} 1\n")));
+
+        // 响应体只写入了一部分就被丢弃。
+        let res = app.call(request(Method::GET, "/stream")).await.unwrap();
+        let mut body = res.into_body();
+        let frame = body.next().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), "hello");
+        let labels = r#"method="GET",route="/stream",status="200""#;
+        assert!(!registry
+            .render()
+            .contains(&format!("http_response_size_bytes_count{{{labels}}}")));
+        drop(body);
+        let out = registry.render();
+        assert!(out.contains(&format!("http_response_size_bytes_sum{{{labels}}} 5\n")));
+        assert!(out.contains(&format!("http_response_size_bytes_count{{{labels}}} 1\n")));
+    }
+
+    #[tokio::test]
+    async fn exposition() {
+        let registry = Metrics::new().namespace("app");
+        let app = app(&registry);
+
+        let res = app.call(request(Method::GET, "/metrics")).await.unwrap();
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let out = std::str::from_utf8(&body).unwrap();
+        assert!(out.contains("# TYPE app_http_requests_total counter\n"));
+        assert!(out.contains("app_http_requests_in_flight{method=\"GET\"} 1\n"));
+    }
+
+    #[test]
+    fn render() {
+        let metrics = Metrics::new().duration_buckets([1.0, 0.1]).size_buckets([]);
+        let key = Key {
+            route: "/a\"b".into(),
+            method: "GET",
+            status: 200,
+        };
+        metrics.observe_request(&key, 0.5);
+        metrics.observe_request(&key, 2.0);
+        metrics.observe_size(&key, 5);
+
+        let out = metrics.render();
+        let labels = r#"method="GET",route="/a\"b",status="200""#;
+        assert!(out.contains(&format!("http_requests_total{{{labels}}} 2\n")));
+        assert!(out.contains(&format!(
+            "http_request_duration_seconds_bucket{{{labels},le=\"0.1\"}} 0\n\
+             http_request_duration_seconds_bucket{{{labels},le=\"1\"}} 1\n\
+             http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 2\n\
+             http_request_duration_seconds_sum{{{labels}}} 2.5\n\
+             http_request_duration_seconds_count{{{labels}}} 2\n"
+        )));
+        assert!(out.contains(&format!("http_response_size_bytes_count{{{labels}}} 1\n")));
+    }
+}
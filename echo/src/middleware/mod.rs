@@ -1,4 +1,95 @@
 mod add_extension;
 pub use add_extension::{add_extension, AddExtension, AddExtensionMiddleware};
 
+#[cfg(feature = "auth")]
+mod auth;
+#[cfg(feature = "auth")]
+pub use auth::{
+    basic_auth, bearer_auth, Auth, AuthError, AuthMiddleware, AuthScheme, Basic, BasicCredentials,
+    Bearer,
+};
+#[cfg(feature = "jwt")]
+pub use auth::{jwt_auth, JwtKeyError, JwtValidator};
+
+pub(crate) mod body_limit;
+pub use body_limit::{
+    body_limit, disable_body_limit, BodyLimit, BodyLimitMiddleware, DEFAULT_BODY_LIMIT,
+};
+
+mod catch_panic;
+pub use catch_panic::{catch_panic, CatchPanic, CatchPanicMiddleware};
+
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+pub use compression::{compression, Compression, CompressionMiddleware};
+
+#[cfg(feature = "concurrency-limit")]
+mod concurrency_limit;
+#[cfg(feature = "concurrency-limit")]
+pub use concurrency_limit::{
+    concurrency_limit, AdaptiveLimit, ConcurrencyLimit, ConcurrencyLimitMiddleware,
+};
+
+#[cfg(feature = "cors")]
+mod cors;
+#[cfg(feature = "cors")]
+pub use cors::{cors, AllowOrigin, Cors, CorsMiddleware};
+
+#[cfg(feature = "csrf")]
+mod csrf;
+#[cfg(feature = "csrf")]
+pub use csrf::{csrf, Csrf, CsrfError, CsrfMiddleware, CsrfToken, X_CSRF_TOKEN};
+
+#[cfg(feature = "decompression")]
+mod decompression;
+#[cfg(feature = "decompression")]
+pub use decompression::{decompression, Decompression, DecompressionMiddleware};
+
+#[cfg(any(feature = "compression", feature = "decompression"))]
+mod encoding;
+
+mod from_fn;
+pub use from_fn::{
+    from_fn, from_fn_with_state, FromFn, FromFnHandler, FromFnMiddleware, Next, WithState,
+};
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::{metrics, Metrics, MetricsMiddleware, MetricsService};
+
+#[cfg(feature = "rate-limit")]
+mod rate_limit;
+#[cfg(feature = "rate-limit")]
+pub use rate_limit::{
+    rate_limit, MemoryRateLimitStore, Quota, RateLimit, RateLimitDecision, RateLimitMiddleware,
+    RateLimitStore,
+};
+
+#[cfg(feature = "request-id")]
+mod request_id;
+#[cfg(feature = "request-id")]
+pub use request_id::{
+    request_id, RequestId, RequestIdError, RequestIdMiddleware, RequestIdService, X_REQUEST_ID,
+};
+
+#[cfg(feature = "session")]
+mod session;
+#[cfg(feature = "session")]
+pub use session::{
+    session, MemoryStore, Session, SessionError, SessionMiddleware, SessionRecord, SessionService,
+    SessionStore,
+};
+
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "tracing")]
+pub use trace::{trace, Trace, TraceMiddleware};
+
+#[cfg(feature = "timeout")]
+mod timeout;
+#[cfg(feature = "timeout")]
+pub use timeout::{timeout, Timeout, TimeoutError, TimeoutMiddleware};
+
 pub use echo_core::middleware::{middleware_fn, Middleware, MiddlewareFn};
@@ -0,0 +1,579 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use echo_core::http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+use crate::extract::RemoteAddr;
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// 内存存储清理空闲键的间隔。
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 按键限制请求速率。
+///
+/// 使用 GCRA 算法，等价于容量为 [`burst`](Quota::burst)、每 `period / limit` 恢复一个令牌的令牌桶。
+/// 默认按照对端 IP 区分客户端，参见 [`RemoteAddr`]；无法得到键的请求不受限制。
+///
+/// 超过限制时返回 `429 Too Many Requests`，包含 `Retry-After` 头；所有受限制的响应都包含
+/// `RateLimit-Limit`、`RateLimit-Remaining` 和 `RateLimit-Reset` 头，`RateLimit-Reset`
+/// 为配额完全恢复所需的秒数。
+///
+/// 默认使用 [`MemoryRateLimitStore`]，多个实例之间共享配额时可以实现 [`RateLimitStore`]。
+///
+/// # 例子
+///
+/// ```
+/// use echo::handler::handler;
+/// use echo::http::header::HeaderName;
+/// use echo::middleware::{rate_limit, Quota};
+/// use echo::route::{get, Router};
+/// use echo::service::ServiceExt;
+///
+/// async fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Router::new().route("/", get(handler(index))).with(
+///     rate_limit(Quota::per_minute(60).burst(10))
+///         .key_by_header(HeaderName::from_static("x-api-key")),
+/// );
+/// ```
+#[inline]
+pub fn rate_limit(quota: Quota) -> RateLimitMiddleware {
+    RateLimitMiddleware::new(quota)
+}
+
+/// 速率限制的配额：每 `period` 允许 `limit` 个请求，最多允许连续 `burst` 个请求。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    limit: u32,
+    period: Duration,
+    burst: u32,
+}
+
+impl Quota {
+    /// 每 `period` 允许 `limit` 个请求，`burst` 默认等于 `limit`。
+    ///
+    /// # Panics
+    ///
+    /// `limit` 为 0 或者 `period` 为 0 时 panic。
+    pub fn new(limit: u32, period: Duration) -> Self {
+        assert!(limit > 0, "rate limit must be greater than 0");
+        assert!(
+            !period.is_zero(),
+            "rate limit period must be greater than 0"
+        );
+        Self {
+            limit,
+            period,
+            burst: limit,
+        }
+    }
+
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    pub fn per_hour(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60 * 60))
+    }
+
+    /// 设置最多允许连续的请求数。
+    ///
+    /// # Panics
+    ///
+    /// `burst` 为 0 时 panic。
+    pub fn burst(mut self, burst: u32) -> Self {
+        assert!(burst > 0, "rate limit burst must be greater than 0");
+        self.burst = burst;
+        self
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// 恢复一个令牌所需的时间。
+    pub fn interval(&self) -> Duration {
+        self.period / self.limit
+    }
+
+    /// 按照 GCRA 算法为一个请求消耗配额。
+    ///
+    /// `tat` 为上次保存的理论到达时间，`now` 为当前时间，两者是相对于同一起点的时长。
+    /// 返回结果和新的理论到达时间，请求被拒绝时后者与 `tat` 相同。
+    ///
+    /// 自定义的 [`RateLimitStore`] 可以用它实现与 [`MemoryRateLimitStore`] 相同的行为。
+    pub fn gcra(&self, tat: Option<Duration>, now: Duration) -> (RateLimitDecision, Duration) {
+        let interval = self.interval();
+        let tolerance = interval * self.burst;
+        let tat = tat.map_or(now, |tat| tat.max(now));
+        let next = tat + interval;
+        let allow_at = next.saturating_sub(tolerance);
+
+        if now < allow_at {
+            let decision = RateLimitDecision {
+                allowed: false,
+                limit: self.burst,
+                remaining: 0,
+                reset: tat - now,
+                retry_after: allow_at - now,
+            };
+            return (decision, tat);
+        }
+
+        let remaining = (now + tolerance - next).as_nanos() / interval.as_nanos();
+        let decision = RateLimitDecision {
+            allowed: true,
+            limit: self.burst,
+            remaining: remaining as u32,
+            reset: next - now,
+            retry_after: Duration::ZERO,
+        };
+        (decision, next)
+    }
+}
+
+/// 一次配额检查的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// 是否允许该请求。
+    pub allowed: bool,
+    /// 最多允许连续的请求数。
+    pub limit: u32,
+    /// 剩余的请求数。
+    pub remaining: u32,
+    /// 配额完全恢复所需的时间。
+    pub reset: Duration,
+    /// 请求被拒绝时需要等待的时间。
+    pub retry_after: Duration,
+}
+
+/// 速率限制的存储后端。
+///
+/// [`check`](RateLimitStore::check) 需要原子地读取、更新键的状态，通常借助 [`Quota::gcra`] 实现。
+pub trait RateLimitStore: Send + Sync + 'static {
+    fn check<'a>(
+        &'a self,
+        key: &'a str,
+        quota: &'a Quota,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, BoxError>>;
+}
+
+impl<T> RateLimitStore for Arc<T>
+where
+    T: RateLimitStore + ?Sized,
+{
+    fn check<'a>(
+        &'a self,
+        key: &'a str,
+        quota: &'a Quota,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, BoxError>> {
+        (**self).check(key, quota)
+    }
+}
+
+/// 将配额状态保存在内存中的存储后端，只在当前进程内有效。
+///
+/// 配额完全恢复的键是空闲的，删除它们不影响结果。检查配额时每隔一段时间会删除空闲的键，
+/// 也可以定期调用 [`cleanup`](MemoryRateLimitStore::cleanup) 清理。
+#[derive(Debug, Clone)]
+pub struct MemoryRateLimitStore {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Debug)]
+struct MemoryState {
+    start: Instant,
+    last_sweep: Duration,
+    tats: HashMap<String, Duration>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MemoryState {
+                start: Instant::now(),
+                last_sweep: Duration::ZERO,
+                tats: HashMap::new(),
+            })),
+        }
+    }
+
+    /// 删除所有空闲的键。
+    pub fn cleanup(&self) {
+        self.state.lock().unwrap().sweep();
+    }
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryState {
+    fn sweep(&mut self) {
+        let now = self.start.elapsed();
+        self.tats.retain(|_, tat| *tat > now);
+        self.last_sweep = now;
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    fn check<'a>(
+        &'a self,
+        key: &'a str,
+        quota: &'a Quota,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, BoxError>> {
+        let mut state = self.state.lock().unwrap();
+        let now = state.start.elapsed();
+        if now - state.last_sweep >= SWEEP_INTERVAL {
+            state.sweep();
+        }
+
+        let (decision, tat) = quota.gcra(state.tats.get(key).copied(), now);
+        if decision.allowed {
+            state.tats.insert(key.to_owned(), tat);
+        }
+        Box::pin(std::future::ready(Ok(decision)))
+    }
+}
+
+type KeyFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+#[derive(Clone)]
+enum Key {
+    PeerIp,
+    Header(HeaderName),
+    Custom(KeyFn),
+}
+
+impl Key {
+    fn extract(&self, req: &Request) -> Option<String> {
+        match self {
+            Key::PeerIp => req
+                .extensions()
+                .get::<RemoteAddr>()
+                .map(|addr| addr.0.ip().to_string()),
+            Key::Header(name) => req
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned),
+            Key::Custom(f) => f(req),
+        }
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::PeerIp => f.write_str("PeerIp"),
+            Key::Header(name) => f.debug_tuple("Header").field(name).finish(),
+            Key::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimitMiddleware<St = MemoryRateLimitStore> {
+    quota: Quota,
+    key: Key,
+    store: Arc<St>,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            key: Key::PeerIp,
+            store: Arc::new(MemoryRateLimitStore::new()),
+        }
+    }
+}
+
+impl<St> RateLimitMiddleware<St> {
+    /// 使用其他存储后端。
+    pub fn store<T>(self, store: T) -> RateLimitMiddleware<T>
+    where
+        T: RateLimitStore,
+    {
+        RateLimitMiddleware {
+            quota: self.quota,
+            key: self.key,
+            store: Arc::new(store),
+        }
+    }
+
+    /// 按照对端 IP 区分客户端，这是默认行为。
+    pub fn key_by_peer_ip(mut self) -> Self {
+        self.key = Key::PeerIp;
+        self
+    }
+
+    /// 按照请求头区分客户端，不包含该请求头的请求不受限制。
+    pub fn key_by_header(mut self, name: HeaderName) -> Self {
+        self.key = Key::Header(name);
+        self
+    }
+
+    /// 按照请求扩展中的值区分客户端，例如认证中间件保存的用户，不包含该扩展的请求不受限制。
+    pub fn key_by_extension<T>(mut self) -> Self
+    where
+        T: fmt::Display + Send + Sync + 'static,
+    {
+        self.key = Key::Custom(Arc::new(|req: &Request| {
+            req.extensions().get::<T>().map(ToString::to_string)
+        }));
+        self
+    }
+
+    /// 使用自定义的函数区分客户端，返回 `None` 的请求不受限制。
+    pub fn key_by<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = Key::Custom(Arc::new(f));
+        self
+    }
+}
+
+impl<St> Clone for RateLimitMiddleware<St> {
+    fn clone(&self) -> Self {
+        Self {
+            quota: self.quota,
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S, St> Middleware<S> for RateLimitMiddleware<St> {
+    type Service = RateLimit<S, St>;
+
+    fn transform(self, service: S) -> Self::Service {
+        RateLimit {
+            service,
+            quota: self.quota,
+            key: self.key,
+            store: self.store,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimit<S, St> {
+    service: S,
+    quota: Quota,
+    key: Key,
+    store: Arc<St>,
+}
+
+impl<S, St> Clone for RateLimit<S, St>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            quota: self.quota,
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S, St> Service<Request> for RateLimit<S, St>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+    St: RateLimitStore,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let key = self.key.extract(&req);
+
+        Box::pin(async move {
+            let Some(key) = key else {
+                return self.service.call(req).await.map_err(Into::into);
+            };
+
+            let decision = self.store.check(&key, &self.quota).await?;
+            let mut res = if decision.allowed {
+                self.service.call(req).await.map_err(Into::into)?
+            } else {
+                let mut res = StatusCode::TOO_MANY_REQUESTS.into_response();
+                res.headers_mut()
+                    .insert(RETRY_AFTER, seconds(decision.retry_after));
+                res
+            };
+            set_headers(res.headers_mut(), &decision);
+            Ok(res)
+        })
+    }
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(RATELIMIT_LIMIT, decision.limit.into());
+    headers.insert(RATELIMIT_REMAINING, decision.remaining.into());
+    headers.insert(RATELIMIT_RESET, seconds(decision.reset));
+}
+
+/// 向上取整的秒数。
+fn seconds(duration: Duration) -> HeaderValue {
+    let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    secs.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::service::service_fn;
+
+    use super::*;
+
+    const API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+    fn service<St: RateLimitStore>(
+        middleware: RateLimitMiddleware<St>,
+    ) -> impl Service<Request, Response = Response, Error = BoxError> {
+        middleware.transform(service_fn(|_: Request| async {
+            Ok::<_, BoxError>("hello".into_response())
+        }))
+    }
+
+    fn request(key: &'static str) -> Request {
+        Request::builder()
+            .header(API_KEY, key)
+            .body(Default::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn gcra() {
+        let quota = Quota::per_second(2);
+        let now = Duration::from_secs(10);
+
+        let (decision, tat) = quota.gcra(None, now);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 1);
+        assert_eq!(decision.reset, Duration::from_millis(500));
+
+        let (decision, tat) = quota.gcra(Some(tat), now);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert_eq!(decision.reset, Duration::from_secs(1));
+
+        let (decision, rejected) = quota.gcra(Some(tat), now);
+        assert!(!decision.allowed);
+        assert_eq!(rejected, tat);
+        assert_eq!(decision.retry_after, Duration::from_millis(500));
+
+        let (decision, _) = quota.gcra(Some(tat), now + Duration::from_millis(500));
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn too_many_requests() {
+        let svc = service(rate_limit(Quota::per_minute(2)).key_by_header(API_KEY));
+
+        let res = svc.call(request("a")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[RATELIMIT_LIMIT], "2");
+        assert_eq!(res.headers()[RATELIMIT_REMAINING], "1");
+        assert_eq!(res.headers()[RATELIMIT_RESET], "30");
+        assert!(!res.headers().contains_key(RETRY_AFTER));
+
+        let res = svc.call(request("a")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[RATELIMIT_REMAINING], "0");
+        assert_eq!(res.headers()[RATELIMIT_RESET], "60");
+
+        let res = svc.call(request("a")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[RETRY_AFTER], "30");
+        assert_eq!(res.headers()[RATELIMIT_LIMIT], "2");
+        assert_eq!(res.headers()[RATELIMIT_REMAINING], "0");
+
+        // 其他键的配额不受影响。
+        let res = svc.call(request("b")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[RATELIMIT_REMAINING], "1");
+    }
+
+    #[tokio::test]
+    async fn unkeyed() {
+        let svc = service(rate_limit(Quota::per_minute(1)));
+        for _ in 0..3 {
+            let res = svc.call(Request::default()).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(!res.headers().contains_key(RATELIMIT_LIMIT));
+        }
+
+        let mut req = Request::default();
+        req.extensions_mut()
+            .insert(RemoteAddr(([127, 0, 0, 1], 8080).into()));
+        let res = svc.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[RATELIMIT_REMAINING], "0");
+
+        // 同一 IP 的不同端口共享配额。
+        let mut req = Request::default();
+        req.extensions_mut()
+            .insert(RemoteAddr(([127, 0, 0, 1], 8081).into()));
+        let res = svc.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn sweep() {
+        let store = MemoryRateLimitStore::new();
+        {
+            let mut state = store.state.lock().unwrap();
+            state.start = Instant::now() - Duration::from_secs(120);
+            state.tats.insert("idle".to_owned(), Duration::from_secs(100));
+            state
+                .tats
+                .insert("busy".to_owned(), Duration::from_secs(1000));
+        }
+
+        // 距离上次清理超过 `SWEEP_INTERVAL`，检查配额时会删除空闲的键。
+        store.check("new", &Quota::per_minute(1)).await.unwrap();
+        let state = store.state.lock().unwrap();
+        let mut keys = state.tats.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["busy", "new"]);
+        assert!(state.last_sweep >= Duration::from_secs(120));
+        drop(state);
+
+        store.state.lock().unwrap().start = Instant::now() - Duration::from_secs(2000);
+        store.cleanup();
+        assert!(store.state.lock().unwrap().tats.is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+use std::fmt;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use echo_core::http::header::{HeaderName, HeaderValue};
+use echo_core::http::request::Parts;
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+
+use crate::extract::{ExtractExtensionError, FromRequestParts};
+
+/// 默认的请求 ID 头。
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 请求中合法的 ID 的最大长度，超过时会生成新的 ID。
+const MAX_LEN: usize = 128;
+
+/// 为每个请求分配 ID。
+///
+/// 请求中已有 ID 头（默认为 `x-request-id`）时沿用该 ID，否则生成新的 ID，默认使用 UUID v4。
+/// ID 以 [`RequestId`] 的形式保存在请求扩展中，可以直接作为提取器使用，并且会被设置到响应头中。
+///
+/// 请求中的 ID 为空、超过 128 个字节或者包含不可见字符时会被忽略；
+/// 自定义的生成函数返回这样的 ID 时会改为使用 UUID v4。
+///
+/// 内部服务返回的错误会被包装为 [`RequestIdError`]，错误处理时可以通过
+/// [`into_response_with`](RequestIdError::into_response_with) 在转换后的响应中设置 ID。
+/// 路由返回的 [`RouteError`](crate::route::RouteError) 中的请求同样带有 [`RequestId`]。
+///
+/// # 例子
+///
+/// ```
+/// use echo::handler::handler;
+/// use echo::middleware::{request_id, RequestId};
+/// use echo::route::{get, Router};
+/// use echo::service::ServiceExt;
+///
+/// async fn index(id: RequestId) -> String {
+///     format!("request id: {id}")
+/// }
+///
+/// let app = Router::new()
+///     .route("/", get(handler(index)))
+///     .with(request_id().uuid_v7());
+/// ```
+///
+/// 将错误转换为带有请求 ID 的响应：
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use echo::http::StatusCode;
+/// use echo::middleware::{request_id, RequestIdError};
+/// use echo::response::{IntoResponse, Response};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+/// use echo::BoxError;
+///
+/// fn error_response(_: BoxError) -> Response {
+///     StatusCode::INTERNAL_SERVER_ERROR.into_response()
+/// }
+///
+/// let app = Router::new()
+///     .with(request_id())
+///     .or_else(|e: BoxError| async move {
+///         let res = match e.downcast::<RequestIdError>() {
+///             Ok(e) => e.into_response_with(error_response),
+///             Err(e) => error_response(e),
+///         };
+///         Ok::<_, Infallible>(res)
+///     });
+/// ```
+#[inline]
+pub fn request_id() -> RequestIdMiddleware {
+    RequestIdMiddleware::new()
+}
+
+#[derive(Clone)]
+pub struct RequestIdMiddleware {
+    header: HeaderName,
+    generator: Generator,
+}
+
+#[derive(Clone)]
+enum Generator {
+    UuidV4,
+    UuidV7,
+    Ulid,
+    Custom(Arc<dyn Fn() -> String + Send + Sync>),
+}
+
+impl RequestIdMiddleware {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            header: X_REQUEST_ID,
+            generator: Generator::UuidV4,
+        }
+    }
+
+    /// 设置读取和写入 ID 的请求头，默认为 `x-request-id`。
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// 使用 UUID v4 生成 ID，这是默认行为。
+    pub fn uuid_v4(mut self) -> Self {
+        self.generator = Generator::UuidV4;
+        self
+    }
+
+    /// 使用 UUID v7 生成 ID，ID 按时间排序。
+    pub fn uuid_v7(mut self) -> Self {
+        self.generator = Generator::UuidV7;
+        self
+    }
+
+    /// 使用 ULID 生成 ID，ID 按时间排序。
+    pub fn ulid(mut self) -> Self {
+        self.generator = Generator::Ulid;
+        self
+    }
+
+    /// 使用自定义的函数生成 ID，返回值应当由 1 到 128 个可见的 ASCII 字符组成，否则会改为使用 UUID v4。
+    pub fn generator<F>(mut self, f: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.generator = Generator::Custom(Arc::new(f));
+        self
+    }
+}
+
+impl Default for RequestIdMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Middleware<S> for RequestIdMiddleware {
+    type Service = RequestIdService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        RequestIdService {
+            service,
+            header: self.header,
+            generator: self.generator,
+        }
+    }
+}
+
+impl fmt::Debug for RequestIdMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestIdMiddleware")
+            .field("header", &self.header)
+            .field("generator", &self.generator)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestIdService<S> {
+    service: S,
+    header: HeaderName,
+    generator: Generator,
+}
+
+impl<S> RequestIdService<S> {
+    fn incoming(&self, req: &Request) -> Option<RequestId> {
+        let value = req.headers().get(&self.header)?;
+        is_valid(value.as_bytes()).then(|| RequestId(value.clone()))
+    }
+
+    fn generate(&self) -> RequestId {
+        let id = match &self.generator {
+            Generator::UuidV4 => uuid::Uuid::new_v4().to_string(),
+            Generator::UuidV7 => uuid::Uuid::now_v7().to_string(),
+            Generator::Ulid => ulid::Ulid::new().to_string(),
+            Generator::Custom(f) => {
+                let id = f();
+                if is_valid(id.as_bytes()) {
+                    id
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(id, "generated request id is invalid, using uuid v4 instead");
+                    uuid::Uuid::new_v4().to_string()
+                }
+            }
+        };
+        // 只包含可见的 ASCII 字符，总是合法的请求头的值。
+        RequestId(HeaderValue::try_from(id).unwrap())
+    }
+}
+
+fn is_valid(id: &[u8]) -> bool {
+    !id.is_empty() && id.len() <= MAX_LEN && id.iter().all(u8::is_ascii_graphic)
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request) -> Self::Future<'_> {
+        let id = self.incoming(&req).unwrap_or_else(|| self.generate());
+        req.extensions_mut().insert(id.clone());
+
+        Box::pin(async move {
+            match self.service.call(req).await {
+                Ok(mut res) => {
+                    res.headers_mut()
+                        .entry(&self.header)
+                        .or_insert(id.into_header_value());
+                    Ok(res)
+                }
+                Err(e) => Err(RequestIdError {
+                    id,
+                    header: self.header.clone(),
+                    inner: e.into(),
+                }
+                .into()),
+            }
+        })
+    }
+}
+
+impl<S> fmt::Debug for RequestIdService<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestIdService")
+            .field("service", &self.service)
+            .field("header", &self.header)
+            .field("generator", &self.generator)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Generator::UuidV4 => f.write_str("UuidV4"),
+            Generator::UuidV7 => f.write_str("UuidV7"),
+            Generator::Ulid => f.write_str("Ulid"),
+            Generator::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// [`request_id`] 中间件包装的内部服务的错误，带有该请求的 ID。
+#[derive(Debug)]
+pub struct RequestIdError {
+    id: RequestId,
+    header: HeaderName,
+    inner: BoxError,
+}
+
+impl RequestIdError {
+    #[inline]
+    pub fn id(&self) -> &RequestId {
+        &self.id
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> BoxError {
+        self.inner
+    }
+
+    /// 使用 `f` 将内部的错误转换为响应，并在响应中设置请求 ID。
+    pub fn into_response_with<F>(self, f: F) -> Response
+    where
+        F: FnOnce(BoxError) -> Response,
+    {
+        let mut res = f(self.inner);
+        res.headers_mut()
+            .entry(self.header)
+            .or_insert(self.id.into_header_value());
+        res
+    }
+}
+
+impl fmt::Display for RequestIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl std::error::Error for RequestIdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.inner)
+    }
+}
+
+/// 请求 ID，由 [`request_id`] 中间件保存在请求扩展中。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // 只保存可见的 ASCII 字符，参见 `RequestIdService::incoming`。
+        self.0.to_str().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_header_value(self) -> HeaderValue {
+        self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromRequestParts for RequestId {
+    type Error = ExtractExtensionError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(parts.extensions.get::<RequestId>().cloned().ok_or(
+            ExtractExtensionError::MissingExtension {
+                type_name: std::any::type_name::<RequestId>(),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{BoxError, Request, Response};
+
+    use super::{request_id, RequestId, RequestIdError, X_REQUEST_ID};
+    use crate::route::RouteError;
+
+    async fn echo(req: Request) -> Result<Response, BoxError> {
+        let id = req.extensions().get::<RequestId>().unwrap();
+        Ok(id.to_string().into_response())
+    }
+
+    fn request(id: &str) -> Request {
+        Request::builder()
+            .header(X_REQUEST_ID, id)
+            .body(Default::default())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn incoming() {
+        let svc = request_id().transform(service_fn(echo));
+
+        let res = svc.call(request("abc-123")).await.unwrap();
+        assert_eq!(res.headers()[X_REQUEST_ID], "abc-123");
+
+        for id in ["", "a b", &"a".repeat(129)] {
+            let res = svc.call(request(id)).await.unwrap();
+            let id = res.headers()[X_REQUEST_ID].to_str().unwrap();
+            assert!(uuid::Uuid::parse_str(id).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn generator() {
+        let svc = request_id()
+            .generator(|| "custom".to_owned())
+            .transform(service_fn(echo));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.headers()[X_REQUEST_ID], "custom");
+
+        let svc = request_id()
+            .generator(|| "line\nbreak".to_owned())
+            .transform(service_fn(echo));
+        let res = svc.call(Request::default()).await.unwrap();
+        let id = res.headers()[X_REQUEST_ID].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn error() {
+        let svc = request_id().transform(service_fn(|req: Request| async {
+            Err::<Response, BoxError>(RouteError::not_found(req).into())
+        }));
+        let e = svc.call(request("abc-123")).await.unwrap_err();
+        let mut e = e.downcast::<RequestIdError>().unwrap();
+        assert_eq!(e.id().as_str(), "abc-123");
+
+        let e = e.inner.downcast_mut::<RouteError>().unwrap();
+        let id = e.request_mut().extensions().get::<RequestId>().unwrap();
+        assert_eq!(id.as_str(), "abc-123");
+
+        let e = svc.call(request("abc-123")).await.unwrap_err();
+        let e = e.downcast::<RequestIdError>().unwrap();
+        let res = e.into_response_with(|_| StatusCode::NOT_FOUND.into_response());
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers()[X_REQUEST_ID], "abc-123");
+    }
+}
@@ -0,0 +1,574 @@
+mod store;
+
+pub use store::{MemoryStore, SessionRecord, SessionStore};
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{ready, Ready};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use echo_core::http::request::Parts;
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cookie::{Cookie, CookieJar, SameSite};
+use crate::extract::{ExtractExtensionError, FromRequestParts};
+use crate::util::{random_token, set_cookie};
+
+/// 创建会话中间件。
+///
+/// 中间件根据 Cookie 中的会话 ID 从 `store` 中加载会话，并将 [`Session`] 插入请求扩展。
+/// 只有会话被修改、轮换 ID 或销毁，以及需要续期时，才会在响应时写回存储并设置 Cookie。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::{session, MemoryStore, Session};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+/// use echo::BoxError;
+///
+/// async fn login(session: Session) -> Result<&'static str, BoxError> {
+///     // 权限变更时轮换会话 ID，防止会话固定攻击。
+///     session.rotate_id();
+///     session.insert("user_id", 42)?;
+///     Ok("ok")
+/// }
+///
+/// let app = Router::new()
+///     .route("/login", echo::handler::handler(login))
+///     .with(session(MemoryStore::new()).cookie_name("sid"));
+/// ```
+#[inline]
+pub fn session<St>(store: St) -> SessionMiddleware<St>
+where
+    St: SessionStore,
+{
+    SessionMiddleware::new(store)
+}
+
+#[derive(Debug, Clone)]
+struct SessionConfig {
+    cookie_name: Cow<'static, str>,
+    path: Cow<'static, str>,
+    domain: Option<Cow<'static, str>>,
+    secure: bool,
+    same_site: SameSite,
+    ttl: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            cookie_name: Cow::Borrowed("session_id"),
+            path: Cow::Borrowed("/"),
+            domain: None,
+            secure: true,
+            same_site: SameSite::Lax,
+            ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl SessionConfig {
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.cookie_name.clone(), value))
+            .path(self.path.clone())
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(self.same_site)
+            .build();
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+}
+
+pub struct SessionMiddleware<St> {
+    store: Arc<St>,
+    config: Arc<SessionConfig>,
+}
+
+impl<St> SessionMiddleware<St> {
+    pub fn new(store: St) -> Self {
+        Self {
+            store: Arc::new(store),
+            config: Default::default(),
+        }
+    }
+
+    /// 设置保存会话 ID 的 Cookie 名称，默认为 `session_id`。
+    pub fn cookie_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        Arc::make_mut(&mut self.config).cookie_name = name.into();
+        self
+    }
+
+    /// 设置 Cookie 的 `Path` 属性，默认为 `/`。
+    pub fn path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        Arc::make_mut(&mut self.config).path = path.into();
+        self
+    }
+
+    /// 设置 Cookie 的 `Domain` 属性，默认不设置。
+    pub fn domain(mut self, domain: impl Into<Cow<'static, str>>) -> Self {
+        Arc::make_mut(&mut self.config).domain = Some(domain.into());
+        self
+    }
+
+    /// 设置 Cookie 的 `Secure` 属性，默认为 `true`。
+    pub fn secure(mut self, secure: bool) -> Self {
+        Arc::make_mut(&mut self.config).secure = secure;
+        self
+    }
+
+    /// 设置 Cookie 的 `SameSite` 属性，默认为 `Lax`。
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        Arc::make_mut(&mut self.config).same_site = same_site;
+        self
+    }
+
+    /// 设置会话的有效期，默认为一天。
+    ///
+    /// 每次保存会话时都会重新计算过期时间，并同步设置 Cookie 的 `Max-Age` 属性。
+    /// 未被修改的会话在剩余有效期不足一半时也会续期，因此只读取会话的请求不会使其过期。
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.config).ttl = ttl;
+        self
+    }
+}
+
+impl<St> Clone for SessionMiddleware<St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, St> Middleware<S> for SessionMiddleware<St> {
+    type Service = SessionService<S, St>;
+
+    fn transform(self, service: S) -> Self::Service {
+        SessionService {
+            service,
+            store: self.store,
+            config: self.config,
+        }
+    }
+}
+
+impl<St> fmt::Debug for SessionMiddleware<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionMiddleware")
+            .field("store", &std::any::type_name::<St>())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+pub struct SessionService<S, St> {
+    service: S,
+    store: Arc<St>,
+    config: Arc<SessionConfig>,
+}
+
+impl<S, St> SessionService<S, St>
+where
+    St: SessionStore,
+{
+    async fn load(&self, id: Option<&str>) -> Result<Session, BoxError> {
+        let record = match id {
+            Some(id) => self.store.load(id).await?,
+            None => None,
+        };
+        Ok(Session::new(record.filter(|record| !record.is_expired())))
+    }
+
+    async fn persist(
+        &self,
+        session: Session,
+        has_cookie: bool,
+        res: &mut Response,
+    ) -> Result<(), BoxError> {
+        let inner = session.take();
+        let config = &self.config;
+
+        if inner.destroyed {
+            if let Some(id) = inner.id {
+                self.store.delete(&id).await?;
+            }
+            // 存储中没有对应的会话时，也要清除请求携带的 Cookie。
+            if has_cookie {
+                let mut cookie = config.cookie(String::new());
+                cookie.make_removal();
+                set_cookie(res, &cookie)?;
+            }
+            return Ok(());
+        }
+
+        let renew = inner.expires_at.is_some_and(|expires_at| {
+            let remaining = expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            remaining < config.ttl / 2
+        });
+        if !inner.modified && !inner.rotate && !renew {
+            return Ok(());
+        }
+
+        let id = match inner.id {
+            Some(id) if !inner.rotate => id,
+            old => {
+                if let Some(old) = old {
+                    self.store.delete(&old).await?;
+                }
+                random_token()
+            }
+        };
+
+        let record = SessionRecord {
+            id,
+            data: inner.data,
+            expires_at: SystemTime::now() + config.ttl,
+        };
+        self.store.save(&record).await?;
+
+        let mut cookie = config.cookie(record.id);
+        cookie.set_max_age(config.ttl.try_into().ok());
+        set_cookie(res, &cookie)
+    }
+}
+
+impl<S, St, B> Service<Request<B>> for SessionService<S, St>
+where
+    S: Service<Request<B>, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+    St: SessionStore,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future<'_> {
+        let id = CookieJar::from_headers(req.headers())
+            .get(&self.config.cookie_name)
+            .map(|cookie| cookie.value().to_owned());
+
+        Box::pin(async move {
+            let session = self.load(id.as_deref()).await?;
+            req.extensions_mut().insert(session.clone());
+            let mut res = self.service.call(req).await.map_err(Into::into)?;
+            self.persist(session, id.is_some(), &mut res).await?;
+            Ok(res)
+        })
+    }
+}
+
+impl<S, St> Clone for SessionService<S, St>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            store: self.store.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, St> fmt::Debug for SessionService<S, St>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionService")
+            .field("service", &self.service)
+            .field("store", &std::any::type_name::<St>())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+/// 当前请求的会话，由 [`session`] 中间件插入请求扩展。
+///
+/// 克隆的 `Session` 共享同一份数据，所有修改都会在响应时写回存储。
+#[derive(Debug, Clone)]
+pub struct Session {
+    inner: Arc<Mutex<SessionInner>>,
+}
+
+#[derive(Debug, Default)]
+struct SessionInner {
+    id: Option<String>,
+    data: HashMap<String, Value>,
+    expires_at: Option<SystemTime>,
+    modified: bool,
+    rotate: bool,
+    destroyed: bool,
+}
+
+impl Session {
+    fn new(record: Option<SessionRecord>) -> Self {
+        let inner = match record {
+            Some(record) => SessionInner {
+                id: Some(record.id),
+                data: record.data,
+                expires_at: Some(record.expires_at),
+                ..Default::default()
+            },
+            None => Default::default(),
+        };
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SessionInner> {
+        self.inner.lock().unwrap()
+    }
+
+    fn take(&self) -> SessionInner {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// 会话 ID，新创建的会话在保存前没有 ID。
+    pub fn id(&self) -> Option<String> {
+        self.lock().id.clone()
+    }
+
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>, SessionError>
+    where
+        T: DeserializeOwned,
+    {
+        match self.lock().data.get(key) {
+            Some(value) => T::deserialize(value)
+                .map(Some)
+                .map_err(SessionError::FailedToDeserialize),
+            None => Ok(None),
+        }
+    }
+
+    pub fn insert<T>(&self, key: impl Into<String>, value: T) -> Result<(), SessionError>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value).map_err(SessionError::FailedToSerialize)?;
+        let mut inner = self.lock();
+        inner.data.insert(key.into(), value);
+        inner.modified = true;
+        Ok(())
+    }
+
+    pub fn remove<T>(&self, key: &str) -> Result<Option<T>, SessionError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut inner = self.lock();
+        match inner.data.remove(key) {
+            Some(value) => {
+                inner.modified = true;
+                serde_json::from_value(value)
+                    .map(Some)
+                    .map_err(SessionError::FailedToDeserialize)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 清空会话数据，但保留会话本身。
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.data.clear();
+        inner.modified = true;
+    }
+
+    /// 在响应时为会话分配新的 ID 并删除旧 ID 对应的记录，数据保持不变。
+    ///
+    /// 应在登录等权限变更时调用，以防止会话固定攻击。
+    pub fn rotate_id(&self) {
+        self.lock().rotate = true;
+    }
+
+    /// 在响应时删除会话并使 Cookie 失效。
+    pub fn destroy(&self) {
+        let mut inner = self.lock();
+        inner.data.clear();
+        inner.destroyed = true;
+    }
+}
+
+impl FromRequestParts for Session {
+    type Error = ExtractExtensionError;
+    type Future<'a> = Ready<Result<Self, Self::Error>>;
+
+    fn from_request_parts(parts: &mut Parts) -> Self::Future<'_> {
+        ready(parts.extensions.get::<Session>().cloned().ok_or(
+            ExtractExtensionError::MissingExtension {
+                type_name: std::any::type_name::<Session>(),
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    FailedToSerialize(serde_json::Error),
+    FailedToDeserialize(serde_json::Error),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::FailedToSerialize(e) => {
+                write!(f, "failed to serialize session value ({e})")
+            }
+            SessionError::FailedToDeserialize(e) => {
+                write!(f, "failed to deserialize session value ({e})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::time::{Duration, SystemTime};
+
+    use echo_core::body::BodyExt;
+    use echo_core::http::header::{COOKIE, SET_COOKIE};
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{Request, Response};
+
+    use super::{session, MemoryStore, Session, SessionRecord, SessionStore};
+
+    async fn handle(req: Request) -> Result<Response, Infallible> {
+        let session = req.extensions().get::<Session>().cloned().unwrap();
+        match req.uri().path() {
+            "/login" => session.insert("user", "alice").unwrap(),
+            "/logout" => session.destroy(),
+            _ => {}
+        }
+        let user: Option<String> = session.get("user").unwrap();
+        Ok(user.unwrap_or_default().into_response())
+    }
+
+    async fn call(store: &MemoryStore, path: &str, id: Option<&str>) -> (Option<String>, String) {
+        let svc = session(store.clone())
+            .ttl(Duration::from_secs(100))
+            .transform(service_fn(handle));
+        let mut req = Request::builder().uri(path);
+        if let Some(id) = id {
+            req = req.header(COOKIE, format!("session_id={id}"));
+        }
+        let req = req.body(Default::default()).unwrap();
+        let res = svc.call(req).await.unwrap();
+        let cookie = res
+            .headers()
+            .get(SET_COOKIE)
+            .map(|value| value.to_str().unwrap().to_owned());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (cookie, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn cookie_value(cookie: &str) -> &str {
+        cookie
+            .split(';')
+            .next()
+            .and_then(|pair| pair.strip_prefix("session_id="))
+            .unwrap()
+    }
+
+    fn record(id: &str, expires_at: SystemTime) -> SessionRecord {
+        SessionRecord {
+            id: id.to_owned(),
+            data: [("user".to_owned(), "bob".into())].into(),
+            expires_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let store = MemoryStore::new();
+
+        let (cookie, _) = call(&store, "/login", None).await;
+        let cookie = cookie.unwrap();
+        assert!(cookie.contains("Max-Age=100"));
+        assert!(cookie.contains("HttpOnly"));
+        let id = cookie_value(&cookie);
+
+        let (cookie, body) = call(&store, "/", Some(id)).await;
+        assert_eq!(body, "alice");
+        assert_eq!(cookie, None);
+
+        let (cookie, body) = call(&store, "/", None).await;
+        assert_eq!(body, "");
+        assert_eq!(cookie, None);
+    }
+
+    #[tokio::test]
+    async fn expiry() {
+        let store = MemoryStore::new();
+        let now = SystemTime::now();
+
+        store
+            .save(&record("expired", now - Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let (cookie, body) = call(&store, "/", Some("expired")).await;
+        assert_eq!(body, "");
+        assert_eq!(cookie, None);
+        assert!(store.load("expired").await.unwrap().is_none());
+
+        // 只读取会话的请求在剩余有效期不足一半时续期。
+        store
+            .save(&record("stale", now + Duration::from_secs(10)))
+            .await
+            .unwrap();
+        let (cookie, body) = call(&store, "/", Some("stale")).await;
+        assert_eq!(body, "bob");
+        let cookie = cookie.unwrap();
+        assert_eq!(cookie_value(&cookie), "stale");
+        assert!(cookie.contains("Max-Age=100"));
+        let record = store.load("stale").await.unwrap().unwrap();
+        assert!(record.expires_at > now + Duration::from_secs(90));
+    }
+
+    #[tokio::test]
+    async fn destroy() {
+        let store = MemoryStore::new();
+
+        let (cookie, _) = call(&store, "/login", None).await;
+        let cookie = cookie.unwrap();
+        let id = cookie_value(&cookie);
+
+        let (cookie, _) = call(&store, "/logout", Some(id)).await;
+        assert!(cookie.unwrap().contains("Max-Age=0"));
+        assert!(store.load(id).await.unwrap().is_none());
+
+        let (cookie, _) = call(&store, "/logout", Some("unknown")).await;
+        let cookie = cookie.unwrap();
+        assert!(cookie.starts_with("session_id=;"));
+        assert!(cookie.contains("Max-Age=0"));
+
+        let (cookie, _) = call(&store, "/logout", None).await;
+        assert_eq!(cookie, None);
+    }
+}
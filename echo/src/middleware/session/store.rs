@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use echo_core::service::future::BoxFuture;
+use echo_core::BoxError;
+use serde_json::Value;
+
+/// 持久化的会话数据。
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub id: String,
+    pub data: HashMap<String, Value>,
+    pub expires_at: SystemTime,
+}
+
+impl SessionRecord {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+}
+
+/// 会话的存储后端。
+///
+/// [`load`](SessionStore::load) 可以返回已过期的会话，中间件会将其视为不存在。
+pub trait SessionStore: Send + Sync + 'static {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<SessionRecord>, BoxError>>;
+
+    fn save<'a>(&'a self, record: &'a SessionRecord) -> BoxFuture<'a, Result<(), BoxError>>;
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), BoxError>>;
+}
+
+impl<T> SessionStore for Arc<T>
+where
+    T: SessionStore + ?Sized,
+{
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<SessionRecord>, BoxError>> {
+        (**self).load(id)
+    }
+
+    fn save<'a>(&'a self, record: &'a SessionRecord) -> BoxFuture<'a, Result<(), BoxError>> {
+        (**self).save(record)
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+        (**self).delete(id)
+    }
+}
+
+/// 将会话保存在内存中的存储后端，进程退出后会话会丢失。
+///
+/// 过期的会话在读取时被删除，也可以定期调用 [`cleanup`](MemoryStore::cleanup) 清理。
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    records: Arc<Mutex<HashMap<String, SessionRecord>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 删除所有已过期的会话。
+    pub fn cleanup(&self) {
+        self.records
+            .lock()
+            .unwrap()
+            .retain(|_, record| !record.is_expired());
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<SessionRecord>, BoxError>> {
+        let mut records = self.records.lock().unwrap();
+        let record = match records.get(id) {
+            Some(record) if record.is_expired() => {
+                records.remove(id);
+                None
+            }
+            record => record.cloned(),
+        };
+        Box::pin(async move { Ok(record) })
+    }
+
+    fn save<'a>(&'a self, record: &'a SessionRecord) -> BoxFuture<'a, Result<(), BoxError>> {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(record.id.clone(), record.clone());
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+        self.records.lock().unwrap().remove(id);
+        Box::pin(async { Ok(()) })
+    }
+}
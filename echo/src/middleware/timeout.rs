@@ -0,0 +1,360 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use echo_core::body::{Body, BodyExt, BoxBody, Bytes, Frame, SizeHint};
+use echo_core::http::StatusCode;
+use echo_core::middleware::Middleware;
+use echo_core::response::IntoResponse;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+use tokio::time::{Instant, Sleep};
+
+type ResponseFn = Arc<dyn Fn(TimeoutError) -> Response + Send + Sync>;
+
+/// 限制内部服务返回响应的时间。
+///
+/// 超时后内部服务的 future 会被丢弃，中间件返回 `504 Gateway Timeout` 响应。
+///
+/// 还可以分别限制读取请求体和写入响应体的时间，两者都从请求到达中间件或响应返回时开始计时：
+///
+/// - 读取请求体超时时，提取器会返回包含 [`TimeoutError::RequestBody`] 的错误，
+///   中间件会将其转换为 `408 Request Timeout` 响应；
+/// - 写入响应体超时时响应头已经发出，只能返回 [`TimeoutError::ResponseBody`] 错误并中断连接，
+///   因此不适用于 SSE 等长连接。
+///
+/// 超时响应可以通过 [`on_timeout`](TimeoutMiddleware::on_timeout) 自定义。
+///
+/// # 例子
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use echo::middleware::timeout;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(
+///     timeout(Duration::from_secs(30))
+///         .read_body(Duration::from_secs(10))
+///         .write_body(Duration::from_secs(60)),
+/// );
+/// ```
+///
+/// 自定义超时响应：
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use echo::http::StatusCode;
+/// use echo::middleware::{timeout, TimeoutError};
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+///
+/// let app = Router::new().with(
+///     timeout(Duration::from_secs(30))
+///         .read_body(Duration::from_secs(10))
+///         .on_timeout(|e| match e {
+///             TimeoutError::RequestBody => (StatusCode::REQUEST_TIMEOUT, "request body timed out"),
+///             _ => (StatusCode::SERVICE_UNAVAILABLE, "please try again later"),
+///         }),
+/// );
+/// ```
+#[inline]
+pub fn timeout(duration: Duration) -> TimeoutMiddleware {
+    TimeoutMiddleware::new(duration)
+}
+
+#[derive(Clone)]
+pub struct TimeoutMiddleware {
+    duration: Duration,
+    read_body: Option<Duration>,
+    write_body: Option<Duration>,
+    response: ResponseFn,
+}
+
+impl TimeoutMiddleware {
+    #[inline]
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            read_body: None,
+            write_body: None,
+            response: Arc::new(IntoResponse::into_response),
+        }
+    }
+
+    /// 限制读取请求体的时间。
+    pub fn read_body(mut self, duration: Duration) -> Self {
+        self.read_body = Some(duration);
+        self
+    }
+
+    /// 限制写入响应体的时间。
+    pub fn write_body(mut self, duration: Duration) -> Self {
+        self.write_body = Some(duration);
+        self
+    }
+
+    /// 设置响应超时和读取请求体超时时返回的响应，默认使用 [`TimeoutError`] 的 [`IntoResponse`]。
+    pub fn on_timeout<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(TimeoutError) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response = Arc::new(move |e| f(e).into_response());
+        self
+    }
+}
+
+impl<S> Middleware<S> for TimeoutMiddleware {
+    type Service = Timeout<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Timeout {
+            service,
+            duration: self.duration,
+            read_body: self.read_body,
+            write_body: self.write_body,
+            response: self.response,
+        }
+    }
+}
+
+impl fmt::Debug for TimeoutMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutMiddleware")
+            .field("duration", &self.duration)
+            .field("read_body", &self.read_body)
+            .field("write_body", &self.write_body)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct Timeout<S> {
+    service: S,
+    duration: Duration,
+    read_body: Option<Duration>,
+    write_body: Option<Duration>,
+    response: ResponseFn,
+}
+
+impl<S> Service<Request> for Timeout<S>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, mut req: Request) -> Self::Future<'_> {
+        if let Some(duration) = self.read_body {
+            req =
+                req.map(|body| TimeoutBody::new(body, duration, TimeoutError::RequestBody).boxed());
+        }
+
+        Box::pin(async move {
+            let res = match tokio::time::timeout(self.duration, self.service.call(req)).await {
+                Ok(res) => res.map_err(Into::into),
+                Err(_) => return Ok((self.response)(TimeoutError::Response)),
+            };
+
+            match res {
+                Ok(res) => match self.write_body {
+                    Some(duration) => Ok(res.map(|body| {
+                        TimeoutBody::new(body, duration, TimeoutError::ResponseBody).boxed()
+                    })),
+                    None => Ok(res),
+                },
+                Err(e) => match crate::util::find_source::<TimeoutError>(&*e) {
+                    Some(TimeoutError::RequestBody) => {
+                        Ok((self.response)(TimeoutError::RequestBody))
+                    }
+                    _ => Err(e),
+                },
+            }
+        })
+    }
+}
+
+impl<S> fmt::Debug for Timeout<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("service", &self.service)
+            .field("duration", &self.duration)
+            .field("read_body", &self.read_body)
+            .field("write_body", &self.write_body)
+            .finish()
+    }
+}
+
+/// 超时错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutError {
+    /// 内部服务没有在限定时间内返回响应。
+    Response,
+    /// 没有在限定时间内读取完请求体。
+    RequestBody,
+    /// 没有在限定时间内写入完响应体。
+    ResponseBody,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::Response => f.write_str("request timed out"),
+            TimeoutError::RequestBody => f.write_str("reading the request body timed out"),
+            TimeoutError::ResponseBody => f.write_str("writing the response body timed out"),
+        }
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+impl IntoResponse for TimeoutError {
+    fn into_response(self) -> Response {
+        match self {
+            TimeoutError::RequestBody => StatusCode::REQUEST_TIMEOUT.into_response(),
+            TimeoutError::Response => StatusCode::GATEWAY_TIMEOUT.into_response(),
+            TimeoutError::ResponseBody => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct TimeoutBody {
+        body: BoxBody,
+        #[pin]
+        sleep: Sleep,
+        error: TimeoutError,
+    }
+}
+
+impl TimeoutBody {
+    fn new(body: BoxBody, duration: Duration, error: TimeoutError) -> Self {
+        Self {
+            body,
+            sleep: tokio::time::sleep_until(Instant::now() + duration),
+            error,
+        }
+    }
+}
+
+impl Body for TimeoutBody {
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let this = self.project();
+        if this.sleep.poll(cx).is_ready() {
+            return Poll::Ready(Some(Err((*this.error).into())));
+        }
+        Pin::new(this.body).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use echo_core::body::{BodyExt, BoxBody, Bytes, Frame, StreamBody};
+    use echo_core::http::StatusCode;
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service};
+    use echo_core::{BoxError, Request, Response};
+    use futures_util::stream;
+
+    use super::{timeout, TimeoutError};
+
+    async fn slow(_: Request) -> Result<Response, BoxError> {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok("done".into_response())
+    }
+
+    fn pending_body() -> BoxBody {
+        let stream = stream::pending::<Result<Frame<Bytes>, BoxError>>();
+        BoxBody::new(StreamBody::new(stream))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn response() {
+        let svc = timeout(Duration::from_secs(1)).transform(service_fn(slow));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let svc = timeout(Duration::from_secs(20)).transform(service_fn(slow));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_body() {
+        let svc = timeout(Duration::from_secs(30))
+            .read_body(Duration::from_secs(1))
+            .transform(service_fn(|mut req: Request| async move {
+                crate::extract::bytes(&mut req).await?;
+                Ok::<_, BoxError>("done".into_response())
+            }));
+        let res = svc.call(Request::new(pending_body())).await.unwrap();
+        assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn response_body() {
+        let svc = timeout(Duration::from_secs(30))
+            .write_body(Duration::from_secs(1))
+            .transform(service_fn(|_: Request| async {
+                Ok::<_, BoxError>(Response::new(pending_body()))
+            }));
+        let res = svc.call(Request::default()).await.unwrap();
+        let e = res.into_body().collect().await.unwrap_err();
+        let e = crate::util::find_source::<TimeoutError>(&*e);
+        assert_eq!(e, Some(&TimeoutError::ResponseBody));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn on_timeout() {
+        let middleware = timeout(Duration::from_secs(1))
+            .read_body(Duration::from_secs(1))
+            .on_timeout(|e| match e {
+                TimeoutError::RequestBody => (StatusCode::BAD_REQUEST, "request body"),
+                _ => (StatusCode::SERVICE_UNAVAILABLE, "response"),
+            });
+
+        let svc = middleware.clone().transform(service_fn(slow));
+        let res = svc.call(Request::default()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "response");
+
+        let svc = middleware.transform(service_fn(|mut req: Request| async move {
+            crate::extract::bytes(&mut req).await?;
+            Ok::<_, BoxError>("done".into_response())
+        }));
+        let res = svc.call(Request::new(pending_body())).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
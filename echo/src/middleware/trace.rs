@@ -0,0 +1,601 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use echo_core::body::{Body, BodyExt, BoxBody, Bytes, Frame, SizeHint};
+use echo_core::http::{Method, StatusCode};
+use echo_core::middleware::Middleware;
+use echo_core::service::future::BoxFuture;
+use echo_core::service::Service;
+use echo_core::{BoxError, Request, Response};
+use tracing::field::Empty;
+use tracing::{Instrument, Level, Span};
+
+use crate::route::{MatchedPath, RouteError};
+
+/// `tracing` 的事件宏要求级别为常量。
+macro_rules! event {
+    ($level:expr, $($args:tt)+) => {{
+        let level = $level;
+        if level == Level::TRACE {
+            tracing::event!(Level::TRACE, $($args)+)
+        } else if level == Level::DEBUG {
+            tracing::event!(Level::DEBUG, $($args)+)
+        } else if level == Level::INFO {
+            tracing::event!(Level::INFO, $($args)+)
+        } else if level == Level::WARN {
+            tracing::event!(Level::WARN, $($args)+)
+        } else {
+            tracing::event!(Level::ERROR, $($args)+)
+        }
+    }};
+}
+
+/// 为每个请求创建一个 [`tracing`] span，并在响应时和响应体写入完成时记录事件。
+///
+/// 默认的 span 名为 `request`，级别为 `INFO`，包含以下字段：
+///
+/// - `method`、`uri`、`version`：请求行；
+/// - `route`：匹配的路由模板，参见 [`MatchedPath`]；
+/// - `request_id`：启用 `request-id` 特性且外层使用了 [`request_id`](super::request_id) 中间件时的请求 ID；
+/// - `status`：响应状态码；
+/// - `bytes_in`、`bytes_out`：读取的请求体和写入的响应体的字节数，在响应体写入完成时记录。
+///
+/// 返回响应时记录 `response` 事件，包含从收到请求开始的耗时 `latency`；响应体写入完成时记录
+/// `finished` 事件，其中的 `latency` 包含写入响应体的时间，对流式响应尤其有用。响应体没有写入完成就被
+/// 丢弃时（例如连接断开）记录 `aborted` 事件，但 `HEAD` 请求和响应体为空（例如 `204` 和 `304`）时
+/// 服务器不会读取响应体，此时仍然记录 `finished` 事件。
+///
+/// 事件的级别由状态码决定，默认 `1xx` 到 `3xx` 为 `INFO`，`4xx` 为 `WARN`，`5xx` 为 `ERROR`，
+/// 内部服务返回错误时按照 `5xx` 处理，但 [`RouteError`] 按照 `4xx` 处理。
+///
+/// # 例子
+///
+/// ```
+/// use echo::middleware::trace;
+/// use echo::route::Router;
+/// use echo::service::ServiceExt;
+/// use echo::Request;
+/// use tracing::Level;
+///
+/// let app = Router::new().with(
+///     trace()
+///         .client_error_level(Level::INFO)
+///         .make_span(|req: &Request| {
+///             tracing::info_span!(
+///                 "http",
+///                 method = %req.method(),
+///                 path = req.uri().path(),
+///                 route = tracing::field::Empty,
+///                 status = tracing::field::Empty,
+///             )
+///         }),
+/// );
+/// ```
+#[inline]
+pub fn trace() -> TraceMiddleware {
+    TraceMiddleware::new()
+}
+
+#[derive(Clone, Copy)]
+pub struct TraceMiddleware<F = fn(&Request) -> Span> {
+    make_span: F,
+    levels: Levels,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Levels {
+    success: Level,
+    client_error: Level,
+    server_error: Level,
+}
+
+impl TraceMiddleware {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            make_span: default_span,
+            levels: Levels {
+                success: Level::INFO,
+                client_error: Level::WARN,
+                server_error: Level::ERROR,
+            },
+        }
+    }
+}
+
+impl Default for TraceMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> TraceMiddleware<F> {
+    /// 自定义创建 span 的函数。
+    ///
+    /// 中间件会尝试记录 `route`、`request_id`、`status`、`bytes_in` 和 `bytes_out` 字段，
+    /// 需要这些字段时应当在 span 中用 [`Empty`](tracing::field::Empty) 声明。
+    pub fn make_span<G>(self, f: G) -> TraceMiddleware<G>
+    where
+        G: Fn(&Request) -> Span,
+    {
+        TraceMiddleware {
+            make_span: f,
+            levels: self.levels,
+        }
+    }
+
+    /// 设置 `1xx` 到 `3xx` 响应的事件级别，默认为 `INFO`。
+    pub fn success_level(mut self, level: Level) -> Self {
+        self.levels.success = level;
+        self
+    }
+
+    /// 设置 `4xx` 响应的事件级别，默认为 `WARN`。
+    pub fn client_error_level(mut self, level: Level) -> Self {
+        self.levels.client_error = level;
+        self
+    }
+
+    /// 设置 `5xx` 响应和错误的事件级别，默认为 `ERROR`。
+    pub fn server_error_level(mut self, level: Level) -> Self {
+        self.levels.server_error = level;
+        self
+    }
+}
+
+impl<S, F> Middleware<S> for TraceMiddleware<F> {
+    type Service = Trace<S, F>;
+
+    fn transform(self, service: S) -> Self::Service {
+        Trace {
+            service,
+            make_span: self.make_span,
+            levels: self.levels,
+        }
+    }
+}
+
+impl<F> fmt::Debug for TraceMiddleware<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceMiddleware")
+            .field("make_span", &std::any::type_name::<F>())
+            .field("levels", &self.levels)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Trace<S, F> {
+    service: S,
+    make_span: F,
+    levels: Levels,
+}
+
+impl<S, F> Service<Request> for Trace<S, F>
+where
+    S: Service<Request, Response = Response> + Sync,
+    S::Error: Into<BoxError>,
+    for<'f> S::Future<'f>: Send,
+    F: Fn(&Request) -> Span + Sync,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future<'f> = BoxFuture<'f, Result<Response, BoxError>>
+    where
+        Self: 'f;
+
+    fn call(&self, req: Request) -> Self::Future<'_> {
+        let start = Instant::now();
+        let span = (self.make_span)(&req);
+        let head = req.method() == Method::HEAD;
+
+        #[cfg(feature = "request-id")]
+        if let Some(id) = req.extensions().get::<super::RequestId>() {
+            span.record("request_id", id.as_str());
+        }
+
+        let bytes_in = Arc::new(AtomicU64::new(0));
+        let req = req.map(|body| {
+            CountBody {
+                body,
+                count: bytes_in.clone(),
+            }
+            .boxed()
+        });
+
+        let fut = self.service.call(req).instrument(span.clone());
+
+        Box::pin(async move {
+            match fut.await.map_err(Into::into) {
+                Ok(res) => {
+                    if let Some(matched) = res.extensions().get::<MatchedPath>() {
+                        span.record("route", matched.as_str());
+                    }
+                    span.record("status", res.status().as_u16());
+
+                    let level = self.levels.for_status(res.status());
+                    let latency = start.elapsed();
+                    span.in_scope(|| event!(level, latency = ?latency, "response"));
+
+                    let recorder = Recorder {
+                        span,
+                        level,
+                        start,
+                        bytes_in,
+                        bytes_out: 0,
+                        finished: false,
+                        empty: head || res.body().size_hint().exact() == Some(0),
+                    };
+                    Ok(res.map(|body| TraceBody { body, recorder }.boxed()))
+                }
+                Err(e) => {
+                    let level = match crate::util::find_source::<RouteError>(&*e) {
+                        Some(_) => self.levels.client_error,
+                        None => self.levels.server_error,
+                    };
+                    let latency = start.elapsed();
+                    span.in_scope(|| event!(level, latency = ?latency, error = %e, "error"));
+                    Err(e)
+                }
+            }
+        })
+    }
+}
+
+impl<S, F> fmt::Debug for Trace<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trace")
+            .field("service", &self.service)
+            .field("make_span", &std::any::type_name::<F>())
+            .field("levels", &self.levels)
+            .finish()
+    }
+}
+
+impl Levels {
+    fn for_status(&self, status: StatusCode) -> Level {
+        if status.is_server_error() {
+            self.server_error
+        } else if status.is_client_error() {
+            self.client_error
+        } else {
+            self.success
+        }
+    }
+}
+
+fn default_span(req: &Request) -> Span {
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri(),
+        version = ?req.version(),
+        route = Empty,
+        request_id = Empty,
+        status = Empty,
+        bytes_in = Empty,
+        bytes_out = Empty,
+    )
+}
+
+pin_project_lite::pin_project! {
+    struct CountBody {
+        #[pin]
+        body: BoxBody,
+        count: Arc<AtomicU64>,
+    }
+}
+
+impl Body for CountBody {
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let this = self.project();
+        let frame = std::task::ready!(this.body.poll_frame(cx));
+        if let Some(data) = frame
+            .as_ref()
+            .and_then(|frame| frame.as_ref().ok()?.data_ref())
+        {
+            this.count.fetch_add(data.len() as u64, Ordering::Relaxed);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+/// 响应体写入完成或被丢弃时记录事件。
+struct Recorder {
+    span: Span,
+    level: Level,
+    start: Instant,
+    bytes_in: Arc<AtomicU64>,
+    bytes_out: u64,
+    finished: bool,
+    /// 服务器不会读取响应体，丢弃时按照写入完成处理。
+    empty: bool,
+}
+
+impl Recorder {
+    fn finish(&mut self, error: Option<&BoxError>) {
+        self.finished = true;
+        self.span
+            .record("bytes_in", self.bytes_in.load(Ordering::Relaxed))
+            .record("bytes_out", self.bytes_out);
+
+        let latency = self.start.elapsed();
+        let _guard = self.span.enter();
+        match error {
+            Some(e) => event!(self.level, latency = ?latency, error = %e, "aborted"),
+            None => event!(self.level, latency = ?latency, "finished"),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if !self.finished {
+            if self.empty {
+                self.finish(None);
+            } else {
+                self.finish(Some(&"response body dropped".into()));
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct TraceBody {
+        #[pin]
+        body: BoxBody,
+        recorder: Recorder,
+    }
+}
+
+impl Body for TraceBody {
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        let mut this = self.project();
+        if this.recorder.finished {
+            return Poll::Ready(None);
+        }
+
+        let frame = {
+            let _guard = this.recorder.span.enter();
+            std::task::ready!(this.body.as_mut().poll_frame(cx))
+        };
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.recorder.bytes_out += data.len() as u64;
+                }
+                // 服务器在 `is_end_stream` 返回 `true` 之后不会再读取响应体。
+                if this.body.is_end_stream() {
+                    this.recorder.finish(None);
+                }
+            }
+            Some(Err(e)) => this.recorder.finish(Some(e)),
+            None => this.recorder.finish(None),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.recorder.finished || self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use echo_core::body::{Body, BodyExt, BoxBody};
+    use echo_core::http::{Method, StatusCode};
+    use echo_core::middleware::Middleware;
+    use echo_core::response::IntoResponse;
+    use echo_core::service::{service_fn, Service, ServiceExt};
+    use echo_core::{BoxError, Request, Response};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    use super::trace;
+    use crate::route::{RouteError, Router};
+
+    /// 记录 span 的字段和事件的级别及消息。
+    #[derive(Clone, Default)]
+    struct Recorded {
+        fields: Arc<Mutex<HashMap<String, String>>>,
+        events: Arc<Mutex<Vec<(Level, String)>>>,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Recorded {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            attrs.record(&mut Fields(&mut self.fields.lock().unwrap()));
+        }
+
+        fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            values.record(&mut Fields(&mut self.fields.lock().unwrap()));
+        }
+
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            event.record(&mut Fields(&mut fields));
+            let message = fields.remove("message").unwrap_or_default();
+            let level = *event.metadata().level();
+            self.events.lock().unwrap().push((level, message));
+        }
+    }
+
+    impl Recorded {
+        fn field(&self, name: &str) -> Option<String> {
+            self.fields.lock().unwrap().get(name).cloned()
+        }
+
+        fn take_events(&self) -> Vec<(Level, String)> {
+            std::mem::take(&mut self.events.lock().unwrap())
+        }
+    }
+
+    fn subscriber() -> (Recorded, tracing::subscriber::DefaultGuard) {
+        let recorded = Recorded::default();
+        let subscriber = tracing_subscriber::registry().with(recorded.clone());
+        (recorded, tracing::subscriber::set_default(subscriber))
+    }
+
+    fn request(method: Method, uri: &str, body: &'static str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(BoxBody::new(body.to_owned()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fields() {
+        let (recorded, _guard) = subscriber();
+
+        let app = Router::new()
+            .route(
+                "/users/:id",
+                service_fn(|mut req: Request| async move {
+                    let body = crate::extract::bytes(&mut req).await?;
+                    Ok::<_, BoxError>((StatusCode::CREATED, body).into_response())
+                }),
+            )
+            .with(trace());
+        let res = app
+            .call(request(Method::POST, "/users/1", "hello"))
+            .await
+            .unwrap();
+        res.into_body().collect().await.unwrap();
+
+        assert_eq!(recorded.field("method").unwrap(), "POST");
+        assert_eq!(recorded.field("uri").unwrap(), "/users/1");
+        assert_eq!(recorded.field("route").unwrap(), "/users/:id");
+        assert_eq!(recorded.field("status").unwrap(), "201");
+        assert_eq!(recorded.field("bytes_in").unwrap(), "5");
+        assert_eq!(recorded.field("bytes_out").unwrap(), "5");
+        assert_eq!(
+            recorded.take_events(),
+            [
+                (Level::INFO, "response".to_owned()),
+                (Level::INFO, "finished".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn levels() {
+        let (recorded, _guard) = subscriber();
+
+        let svc = trace().transform(service_fn(|req: Request| async move {
+            match req.uri().path() {
+                "/bad" => Ok(StatusCode::BAD_REQUEST.into_response()),
+                "/missing" => Err(RouteError::not_found(req).into()),
+                _ => Err::<Response, BoxError>("boom".into()),
+            }
+        }));
+
+        let res = svc.call(request(Method::GET, "/bad", "")).await.unwrap();
+        drop(res);
+        assert_eq!(
+            recorded.take_events(),
+            [
+                (Level::WARN, "response".to_owned()),
+                (Level::WARN, "finished".to_owned()),
+            ]
+        );
+
+        svc.call(request(Method::GET, "/missing", ""))
+            .await
+            .unwrap_err();
+        assert_eq!(recorded.take_events(), [(Level::WARN, "error".to_owned())]);
+
+        svc.call(request(Method::GET, "/error", ""))
+            .await
+            .unwrap_err();
+        assert_eq!(recorded.take_events(), [(Level::ERROR, "error".to_owned())]);
+
+        let svc = trace()
+            .client_error_level(Level::DEBUG)
+            .transform(service_fn(|_: Request| async {
+                Ok::<_, BoxError>(StatusCode::NOT_FOUND.into_response())
+            }));
+        svc.call(Request::default()).await.unwrap();
+        assert_eq!(
+            recorded.take_events(),
+            [
+                (Level::DEBUG, "response".to_owned()),
+                (Level::DEBUG, "finished".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_body() {
+        let (recorded, _guard) = subscriber();
+
+        let svc = trace().transform(service_fn(|_: Request| async {
+            Ok::<_, BoxError>("hello".into_response())
+        }));
+
+        // 没有读取的响应体被丢弃。
+        let res = svc.call(request(Method::GET, "/", "")).await.unwrap();
+        drop(res);
+        assert_eq!(recorded.take_events()[1].1, "aborted");
+
+        // 服务器不会读取 `HEAD` 请求的响应体。
+        let res = svc.call(request(Method::HEAD, "/", "")).await.unwrap();
+        drop(res);
+        assert_eq!(recorded.take_events()[1].1, "finished");
+
+        // 读取最后一个数据帧之后 `is_end_stream` 为 `true`，服务器不会继续读取。
+        let res = svc.call(request(Method::GET, "/", "")).await.unwrap();
+        let mut body = res.into_body();
+        body.next().await.unwrap().unwrap();
+        assert!(body.is_end_stream());
+        drop(body);
+        assert_eq!(recorded.take_events()[1].1, "finished");
+    }
+}
@@ -1,12 +1,19 @@
-pub use echo_core::response::{IntoResponse, Response};
+pub use echo_core::response::{IntoResponse, IntoResponseParts, IntoResponseResult, Response};
 
 mod html;
 mod json;
+mod negotiate;
 mod redirect;
 
 pub use html::Html;
 pub use json::Json;
+pub use negotiate::Negotiate;
 pub use redirect::Redirect;
 
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MsgPack;
+
 #[cfg(feature = "sse")]
 pub mod sse;
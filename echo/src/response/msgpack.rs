@@ -0,0 +1,34 @@
+use std::sync::LazyLock;
+
+use echo_core::body::BodyExt;
+use echo_core::http::{header, HeaderValue, StatusCode};
+use echo_core::response::IntoResponse;
+use echo_core::Response;
+use mime::Mime;
+use serde::Serialize;
+
+pub(crate) static APPLICATION_MSGPACK: LazyLock<Mime> =
+    LazyLock::new(|| "application/msgpack".parse().unwrap());
+
+/// 使用 MessagePack 序列化的响应，结构体会被序列化为以字段名为键的映射。
+#[derive(Debug, Clone, Copy)]
+pub struct MsgPack<T>(pub T);
+
+impl<T> IntoResponse for MsgPack<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        match rmp_serde::to_vec_named(&self.0) {
+            Ok(bytes) => {
+                let mut res = Response::new(bytes.boxed());
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/msgpack"),
+                );
+                res
+            }
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+
+use echo_core::http::header::{self, HeaderValue};
+use echo_core::http::StatusCode;
+use echo_core::response::IntoResponse;
+use echo_core::Response;
+use mime::Mime;
+use serde::Serialize;
+
+use super::{Html, Json};
+use crate::extract::Accept;
+
+type Render = Box<dyn FnOnce() -> Response + Send>;
+
+/// 根据请求的 `Accept` 头在处理函数提供的多种表示中选择一种作为响应。
+///
+/// 质量值相同时优先选择先提供的表示，没有可接受的表示时返回 `406 Not Acceptable`。
+/// 只有被选中的表示才会被渲染。
+///
+/// # 例子
+///
+/// ```
+/// use echo::extract::Accept;
+/// use echo::response::Negotiate;
+///
+/// async fn user(accept: Accept) -> Negotiate {
+///     Negotiate::new(accept)
+///         .json(serde_json::json!({ "name": "echo" }))
+///         .html("<p>echo</p>")
+///         .text("echo")
+/// }
+/// ```
+pub struct Negotiate {
+    accept: Accept,
+    offers: Vec<(Mime, Render)>,
+}
+
+impl Negotiate {
+    pub fn new(accept: Accept) -> Self {
+        Self {
+            accept,
+            offers: Vec::new(),
+        }
+    }
+
+    /// 提供 `mime` 类型的表示，`f` 只在该表示被选中时调用。
+    ///
+    /// 如果 `f` 返回的响应没有设置 `Content-Type`，会使用 `mime`。
+    pub fn with<F, T>(mut self, mime: Mime, f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: IntoResponse,
+    {
+        self.offers
+            .push((mime, Box::new(move || f().into_response())));
+        self
+    }
+
+    pub fn json<T>(self, value: T) -> Self
+    where
+        T: Serialize + Send + 'static,
+    {
+        self.with(mime::APPLICATION_JSON, move || Json(value))
+    }
+
+    pub fn html(self, html: impl Into<String>) -> Self {
+        let html = html.into();
+        self.with(mime::TEXT_HTML_UTF_8, move || Html(html))
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        self.with(mime::TEXT_PLAIN_UTF_8, move || text)
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub fn msgpack<T>(self, value: T) -> Self
+    where
+        T: Serialize + Send + 'static,
+    {
+        self.with(super::msgpack::APPLICATION_MSGPACK.clone(), move || {
+            super::MsgPack(value)
+        })
+    }
+}
+
+impl IntoResponse for Negotiate {
+    fn into_response(self) -> Response {
+        let mimes = self
+            .offers
+            .iter()
+            .map(|(mime, _)| mime.clone())
+            .collect::<Vec<_>>();
+
+        let mut res = match self.accept.negotiate(&mimes) {
+            Some(mime) => {
+                let index = mimes.iter().position(|m| m == mime).unwrap();
+                let (_, render) = self.offers.into_iter().nth(index).unwrap();
+                let mut res = render();
+                if let Ok(value) = HeaderValue::try_from(mime.as_ref()) {
+                    res.headers_mut()
+                        .entry(header::CONTENT_TYPE)
+                        .or_insert(value);
+                }
+                res
+            }
+            None => StatusCode::NOT_ACCEPTABLE.into_response(),
+        };
+        crate::util::add_vary(res.headers_mut(), "accept");
+        res
+    }
+}
+
+impl fmt::Debug for Negotiate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Negotiate")
+            .field("accept", &self.accept)
+            .field(
+                "offers",
+                &self.offers.iter().map(|(mime, _)| mime).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use echo_core::body::{BodyExt, Bytes};
+    use echo_core::http::header::{self, HeaderMap, HeaderValue};
+    use echo_core::http::StatusCode;
+    use echo_core::response::IntoResponse;
+    use echo_core::Response;
+
+    use super::Negotiate;
+    use crate::extract::Accept;
+
+    fn negotiate(accept: &'static str) -> Negotiate {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+        Negotiate::new(Accept::from_headers(&headers))
+    }
+
+    fn offers(accept: &'static str) -> Negotiate {
+        negotiate(accept).json("json").html("html").text("text")
+    }
+
+    async fn body(res: Response) -> Bytes {
+        res.into_body().collect().await.unwrap().to_bytes()
+    }
+
+    #[tokio::test]
+    async fn select() {
+        let res = offers("text/html").into_response();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(res.headers()[header::VARY], "accept");
+        assert_eq!(body(res).await, "html");
+
+        // 只渲染被选中的表示。
+        let res = negotiate("text/plain")
+            .with(mime::APPLICATION_JSON, || -> &'static str {
+                unreachable!()
+            })
+            .text("text")
+            .into_response();
+        assert_eq!(body(res).await, "text");
+    }
+
+    #[tokio::test]
+    async fn quality() {
+        let res = offers("application/json;q=0.5, text/plain").into_response();
+        assert_eq!(body(res).await, "text");
+
+        let res = offers("text/*;q=0.8, application/json;q=0.9").into_response();
+        assert_eq!(body(res).await, "\"json\"");
+
+        // 质量值相同时选择先提供的表示。
+        let res = offers("text/plain, text/html").into_response();
+        assert_eq!(body(res).await, "html");
+        let res = offers("*/*").into_response();
+        assert_eq!(body(res).await, "\"json\"");
+    }
+
+    #[tokio::test]
+    async fn not_acceptable() {
+        let res = offers("image/png").into_response();
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(res.headers()[header::VARY], "accept");
+
+        let res = offers("application/json;q=0, text/html;q=0, text/plain;q=0").into_response();
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn vary() {
+        let res = negotiate("text/plain")
+            .with(mime::TEXT_PLAIN, || {
+                let mut res = "text".into_response();
+                res.headers_mut().insert(
+                    header::VARY,
+                    HeaderValue::from_static("Accept-Encoding, Accept"),
+                );
+                res
+            })
+            .into_response();
+        let vary = res
+            .headers()
+            .get_all(header::VARY)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(vary, ["Accept-Encoding, Accept"]);
+    }
+}
@@ -1,5 +1,6 @@
 mod event;
 mod keep_alive;
+#[allow(clippy::module_inception)]
 mod sse;
 
 pub use event::Event;
@@ -53,8 +53,8 @@ impl RouteError {
 impl fmt::Display for RouteError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.kind() {
-            RouteErrorKind::NotFound { .. } => f.write_str("Not Found"),
-            RouteErrorKind::MethodNotAllowed { .. } => f.write_str("Method Not Allowed"),
+            RouteErrorKind::NotFound => f.write_str("Not Found"),
+            RouteErrorKind::MethodNotAllowed => f.write_str("Method Not Allowed"),
         }
     }
 }
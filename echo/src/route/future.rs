@@ -2,26 +2,49 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use echo_core::BoxError;
+use echo_core::{BoxError, Response};
+
+use super::MatchedPath;
 
 pin_project_lite::pin_project! {
     #[project = RouteFutureProj]
     pub enum RouteFuture<Fut> {
-        A { #[pin] fut: Fut },
+        A { #[pin] fut: Fut, matched: Option<MatchedPath> },
         B { err: Option<BoxError> },
     }
 }
 
-impl<Fut, Res, Err> Future for RouteFuture<Fut>
+impl<Fut> RouteFuture<Fut> {
+    /// 响应中没有 [`MatchedPath`] 时设置为 `matched`，内层路由器的匹配结果优先。
+    pub(crate) fn matched(self, matched: MatchedPath) -> Self {
+        match self {
+            RouteFuture::A { fut, .. } => RouteFuture::A {
+                fut,
+                matched: Some(matched),
+            },
+            RouteFuture::B { err } => RouteFuture::B { err },
+        }
+    }
+}
+
+impl<Fut, Err> Future for RouteFuture<Fut>
 where
-    Fut: Future<Output = Result<Res, Err>>,
+    Fut: Future<Output = Result<Response, Err>>,
     Err: Into<BoxError>,
 {
-    type Output = Result<Res, BoxError>;
+    type Output = Result<Response, BoxError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.project() {
-            RouteFutureProj::A { fut } => fut.poll(cx).map_err(Into::into),
+            RouteFutureProj::A { fut, matched } => {
+                let mut res = std::task::ready!(fut.poll(cx)).map_err(Into::into)?;
+                if let Some(matched) = matched.take() {
+                    if res.extensions().get::<MatchedPath>().is_none() {
+                        res.extensions_mut().insert(matched);
+                    }
+                }
+                Poll::Ready(Ok(res))
+            }
             RouteFutureProj::B { err } => Poll::Ready(Err(err
                 .take()
                 .expect("future must not be polled after it returned `Poll::Ready`"))),
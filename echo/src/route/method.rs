@@ -55,12 +55,13 @@ impl Service<Request> for MethodRouter {
                         None
                     }
                 })
-                .or_else(|| router.any.as_ref())
+                .or(router.any.as_ref())
         }
 
         match match_(self, req.method()) {
             Some(service) => RouteFuture::A {
                 fut: service.call(req),
+                matched: None,
             },
             None => RouteFuture::B {
                 err: Some(RouteError::method_not_allowed(req).into()),
@@ -120,6 +121,7 @@ impl<S> MethodRoute<S> {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn add(mut self, method: Method) -> Self {
         self.methods = self.methods.add(method);
         self
@@ -231,7 +233,7 @@ impl MergeToMethodRouter for MethodRoute<ArcService<Request, Response, BoxError>
             }
             Methods::More(methods) => {
                 for method in methods.iter() {
-                    if router.contains(&method) {
+                    if router.contains(method) {
                         return Err(Some(method.clone()));
                     }
                 }
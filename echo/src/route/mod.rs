@@ -10,5 +10,5 @@ pub use method::{
     any, connect, delete, get, head, method, options, patch, post, put, trace, IntoMethodRoute,
     MethodRoute,
 };
-pub use params::PathParams;
+pub use params::{MatchedPath, PathParams};
 pub use router::{Route, Router};
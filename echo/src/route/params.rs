@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use echo_core::http::Extensions;
 use matchit::Params;
 
@@ -17,12 +19,38 @@ impl PathParams {
     }
 }
 
+/// 匹配的路由模板，例如 `/users/:id`，嵌套的路由器会拼接外层范围的前缀。
+///
+/// 路由器会将其保存在请求扩展和响应扩展中，适用于日志和指标等需要低基数标签的场景。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchedPath(Arc<str>);
+
+impl MatchedPath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+pub(crate) fn insert_matched_path(extensions: &mut Extensions, path: &str) -> MatchedPath {
+    let path = path.strip_suffix(PRIVATE_TAIL_PARAM).unwrap_or(path);
+    let matched = match extensions.get::<MatchedPath>() {
+        Some(prefix) => {
+            let prefix = prefix.as_str();
+            let prefix = prefix.strip_suffix("/*").unwrap_or(prefix);
+            MatchedPath(format!("{prefix}{path}").into())
+        }
+        None => MatchedPath(path.into()),
+    };
+    extensions.insert(matched.clone());
+    matched
+}
+
 pub(crate) fn prase_path_params(params: Params) -> (Vec<(String, String)>, Option<String>) {
     params.iter().fold(
         (Vec::with_capacity(params.len()), None),
         |(mut params, mut tail), (k, v)| {
             if k == PRIVATE_TAIL_PARAM {
-                tail = Some(format!("/{}", if v.starts_with('/') { &v[1..] } else { v }));
+                tail = Some(format!("/{}", v.strip_prefix('/').unwrap_or(v)));
             } else {
                 params.push((k.to_owned(), v.to_owned()));
             }
@@ -14,7 +14,7 @@ use super::future::RouteFuture;
 use super::method::{MergeToMethodRouter, MethodRouter};
 use super::{IntoMethodRoute, MethodRoute, RouteError, RouterError};
 
-pub(crate) const PRIVATE_TAIL_PARAM: &'static str = "__private__tail_param";
+pub(crate) const PRIVATE_TAIL_PARAM: &str = "__private__tail_param";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 struct RouteId(u32);
@@ -46,14 +46,13 @@ impl RouterInner {
     }
 
     fn next(&mut self) -> Option<RouteId> {
-        self.id.next().map(|id| {
+        self.id.next().inspect(|&id| {
             self.id = id;
-            id
         })
     }
 
     fn add(&mut self, path: String) -> Result<RouteId, RouterError> {
-        let id = self.next().ok_or_else(|| RouterError::TooManyPath)?;
+        let id = self.next().ok_or(RouterError::TooManyPath)?;
 
         if let Err(e) = self.inner.insert(&path, id) {
             return Err(RouterError::from_insert_error(path, e));
@@ -105,7 +104,7 @@ impl Router {
         if !path.starts_with('/') {
             return Err(RouterError::InvalidPath {
                 path: path.to_owned(),
-                message: format!("path must start with a `/`"),
+                message: "path must start with a `/`".to_string(),
             });
         }
         let path = if path.ends_with('*') {
@@ -145,7 +144,7 @@ impl Router {
         if !path.starts_with('/') {
             return Err(RouterError::InvalidPath {
                 path: path.to_owned(),
-                message: format!("path must start with a `/`"),
+                message: "path must start with a `/`".to_string(),
             });
         }
         let path = if path.ends_with('/') {
@@ -206,7 +205,7 @@ impl Router {
                 let Endpoint::Route(router) = self.table.entry(id).or_insert_with(|| Endpoint::Route(Default::default())) else {
                     return Err(RouterError::Conflict {
                         path,
-                        message: format!("conflict with previously registered route"),
+                        message: "conflict with previously registered route".to_string(),
                     })
                 };
                 service.merge_to(router)
@@ -215,7 +214,7 @@ impl Router {
                 let Endpoint::Scope(router) = self.table.entry(id).or_insert_with(|| Endpoint::Scope(Default::default())) else {
                     return Err(RouterError::Conflict {
                         path,
-                        message: format!("conflict with previously registered route"),
+                        message: "conflict with previously registered route".to_string(),
                     })
                 };
                 service.merge_to(router)
@@ -227,7 +226,7 @@ impl Router {
                 Some(method) => {
                     format!("conflict with previously registered `{method}` HTTP method")
                 }
-                None => format!("conflict with previously registered any HTTP method"),
+                None => "conflict with previously registered any HTTP method".to_string(),
             };
             RouterError::Conflict { path, message }
         })?;
@@ -244,7 +243,7 @@ impl Router {
         Ok(id)
     }
 
-    fn into_arc_service<S>(service: S) -> ArcService<Request, Response, BoxError>
+    pub(crate) fn into_arc_service<S>(service: S) -> ArcService<Request, Response, BoxError>
     where
         S: Service<Request> + Send + Sync + 'static,
         S::Response: IntoResponse,
@@ -278,7 +277,11 @@ impl Service<Request> for Router {
             Ok(Match { value, params }) => {
                 let (params, tail) = super::params::prase_path_params(params);
                 super::params::insert_path_params(req.extensions_mut(), params);
-                match self.table.get(value) {
+                let matched = super::params::insert_matched_path(
+                    req.extensions_mut(),
+                    &self.inner.id_to_path[value],
+                );
+                let fut = match self.table.get(value) {
                     Some(Endpoint::Route(service)) => service.call(req),
                     Some(Endpoint::Scope(service)) => {
                         replace_request_path(&mut req, &tail.unwrap());
@@ -287,7 +290,8 @@ impl Service<Request> for Router {
                     None => RouteFuture::B {
                         err: Some(RouteError::not_found(req).into()),
                     },
-                }
+                };
+                fut.matched(matched)
             }
             Err(_) => RouteFuture::B {
                 err: Some(RouteError::not_found(req).into()),
@@ -337,11 +341,7 @@ impl<S> Route<S> {
 fn replace_request_path(req: &mut Request, path: &str) {
     let uri = req.uri_mut();
 
-    let path = if path.starts_with('/') {
-        path[1..].as_ref()
-    } else {
-        path
-    };
+    let path = path.strip_prefix('/').unwrap_or(path);
 
     let path_and_query = if let Some(query) = uri.query() {
         format!("/{path}?{query}")
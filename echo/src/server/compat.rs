@@ -28,6 +28,10 @@ impl EchoBody for HyperToEcho {
         self.project().body.poll_frame(cx).map_err(From::from)
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         self.body.size_hint()
     }
@@ -60,6 +64,10 @@ impl HyperBody for EchoToHyper {
             .map_err(|e| e.to_string().into())
     }
 
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
     fn size_hint(&self) -> SizeHint {
         self.body.size_hint()
     }
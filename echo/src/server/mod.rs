@@ -14,6 +14,7 @@ use hyper::body::Incoming;
 use hyper::server::conn::http1;
 use tokio::net::TcpListener;
 
+use crate::extract::RemoteAddr;
 use crate::server::compat::{EchoToHyper, HyperToEcho};
 
 #[derive(Debug, Clone)]
@@ -67,15 +68,6 @@ impl Server {
         tokio::pin!(signal);
 
         let service = service.boxed_arc();
-        let service = hyper::service::service_fn(move |req| {
-            let service = service.clone();
-            async move {
-                let service = service
-                    .map_request(|request: Request<Incoming>| request.map(HyperToEcho::to))
-                    .map_ok(|response: S::Response| response.into_response().map(EchoToHyper::to));
-                service.call(req).await
-            }
-        });
 
         let graceful = GracefulShutdown::new();
         let listener = TcpListener::bind(self.options.addr).await?;
@@ -86,9 +78,25 @@ impl Server {
                     break timeout;
                 }
                 conn = listener.accept() => {
-                    let (conn, _) = conn?;
-
-                    let conn = self.http1.serve_connection(conn, service.clone()).with_upgrades();
+                    let (conn, addr) = conn?;
+
+                    let service = service.clone();
+                    let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
+                        req.extensions_mut().insert(RemoteAddr(addr));
+                        let service = service.clone();
+                        async move {
+                            let service = service
+                                .map_request(|request: Request<Incoming>| {
+                                    request.map(HyperToEcho::to)
+                                })
+                                .map_ok(|response: S::Response| {
+                                    response.into_response().map(EchoToHyper::to)
+                                });
+                            service.call(req).await
+                        }
+                    });
+
+                    let conn = self.http1.serve_connection(conn, service).with_upgrades();
                     let conn = graceful.watch(conn);
 
                     tokio::spawn(conn);
@@ -101,3 +109,47 @@ impl Server {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::time::Duration;
+
+    use echo_core::service::service_fn;
+    use echo_core::Request;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::Server;
+    use crate::extract::RemoteAddr;
+
+    #[tokio::test]
+    async fn remote_addr() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let service = service_fn(|req: Request| async move {
+            let RemoteAddr(addr) = *req.extensions().get::<RemoteAddr>().unwrap();
+            Ok::<_, Infallible>(addr.to_string())
+        });
+        tokio::spawn(Server::bind(addr).serve(service));
+
+        let mut stream = loop {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut res = String::new();
+        stream.read_to_string(&mut res).await.unwrap();
+
+        let local = stream.local_addr().unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK"));
+        assert!(res.ends_with(&local.to_string()));
+    }
+}
@@ -6,3 +6,59 @@ pub(crate) fn try_downcast<Src: 'static, Dst: 'static>(src: Src) -> Result<Dst,
         Err(src.unwrap())
     }
 }
+
+/// 在错误及其 `source` 链中查找类型为 `E` 的错误。
+pub(crate) fn find_source<'a, E>(mut e: &'a (dyn std::error::Error + 'static)) -> Option<&'a E>
+where
+    E: std::error::Error + 'static,
+{
+    loop {
+        if let Some(e) = e.downcast_ref::<E>() {
+            return Some(e);
+        }
+        e = e.source()?;
+    }
+}
+
+/// 向 `Vary` 添加 `name`（小写），已经包含 `name` 或 `*` 时不做修改。
+pub(crate) fn add_vary(headers: &mut echo_core::http::HeaderMap, name: &'static str) {
+    let exists = headers
+        .get_all(echo_core::http::header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| {
+            let value = value.trim();
+            value == "*" || value.eq_ignore_ascii_case(name)
+        });
+    if !exists {
+        headers.append(
+            echo_core::http::header::VARY,
+            echo_core::http::HeaderValue::from_static(name),
+        );
+    }
+}
+
+/// 生成 32 字节的随机值，编码为 URL 安全的 base64。
+#[cfg(any(feature = "session", feature = "csrf"))]
+pub(crate) fn random_token() -> String {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use rand::RngCore;
+
+    let mut bytes = [0; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// 在响应中追加 `Set-Cookie` 头。
+#[cfg(any(feature = "session", feature = "csrf"))]
+pub(crate) fn set_cookie(
+    res: &mut echo_core::Response,
+    cookie: &crate::cookie::Cookie<'_>,
+) -> Result<(), echo_core::BoxError> {
+    let value = echo_core::http::HeaderValue::try_from(cookie.encoded().to_string())?;
+    res.headers_mut()
+        .append(echo_core::http::header::SET_COOKIE, value);
+    Ok(())
+}
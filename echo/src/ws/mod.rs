@@ -86,13 +86,13 @@ impl WebSocketUpgrade {
     }
 
     pub fn from_request<B>(req: &mut Request<B>) -> Result<Self, WebSocketUpgradeError> {
-        if !util::header_eq_ignore_case(&req.headers(), header::CONNECTION, "upgrade") {
+        if !util::header_eq_ignore_case(req.headers(), header::CONNECTION, "upgrade") {
             return Err(WebSocketUpgradeError::InvalidConnectionHeader);
         }
-        if !util::header_eq_ignore_case(&req.headers(), header::UPGRADE, "websocket") {
+        if !util::header_eq_ignore_case(req.headers(), header::UPGRADE, "websocket") {
             return Err(WebSocketUpgradeError::InvalidUpgradeHeader);
         }
-        if !util::header_eq(&req.headers(), header::SEC_WEBSOCKET_VERSION, "13") {
+        if !util::header_eq(req.headers(), header::SEC_WEBSOCKET_VERSION, "13") {
             return Err(WebSocketUpgradeError::InvalidWebSocketVersionHeader);
         }
 